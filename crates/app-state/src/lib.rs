@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+pub mod prelude;
+
+use limnus_app::prelude::{App, Plugin};
+use limnus_macros::Resource;
+use limnus_resource::Resource;
+use limnus_scheduler::Scheduler;
+use limnus_stage::{StageId, StageTag, Stages};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use tracing::debug;
+
+/// A set of mutually exclusive application states, usually an enum like `Menu`, `Loading`, `Playing` and `Paused`.
+pub trait States: Debug + Clone + Eq + Hash + Send + Sync + 'static {}
+
+/// The state the app is currently in. Change it with [`NextState`].
+#[derive(Debug, Resource)]
+pub struct State<S: States> {
+    current: S,
+}
+
+impl<S: States> State<S> {
+    #[must_use]
+    pub const fn new(current: S) -> Self {
+        Self { current }
+    }
+
+    #[must_use]
+    pub const fn get(&self) -> &S {
+        &self.current
+    }
+}
+
+/// The state to switch to. The transition is applied at the start of the next update,
+/// before any other scheduler is run.
+#[derive(Debug, Resource)]
+pub struct NextState<S: States> {
+    next: Option<S>,
+}
+
+impl<S: States> Default for NextState<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: States> NextState<S> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { next: None }
+    }
+
+    /// Requests a transition. Setting it again before the transition is applied replaces the request.
+    pub fn set(&mut self, next: S) {
+        self.next = Some(next);
+    }
+
+    #[must_use]
+    pub const fn get(&self) -> Option<&S> {
+        self.next.as_ref()
+    }
+}
+
+/// Runs once when the app enters the state.
+pub struct OnEnter<S: States>(pub S);
+
+impl<S: States> StageTag for OnEnter<S> {
+    const CREATED_ON_DEMAND: bool = true;
+
+    fn stage_id(&self) -> StageId {
+        StageId::with_value::<Self, _>(self.0.clone())
+    }
+}
+
+/// Runs once when the app leaves the state.
+pub struct OnExit<S: States>(pub S);
+
+impl<S: States> StageTag for OnExit<S> {
+    const CREATED_ON_DEMAND: bool = true;
+
+    fn stage_id(&self) -> StageId {
+        StageId::with_value::<Self, _>(self.0.clone())
+    }
+}
+
+/// Runs once when the app goes from one specific state to another,
+/// after [`OnExit`] for `from` and before [`OnEnter`] for `to`.
+pub struct OnTransition<S: States> {
+    pub from: S,
+    pub to: S,
+}
+
+impl<S: States> StageTag for OnTransition<S> {
+    const CREATED_ON_DEMAND: bool = true;
+
+    fn stage_id(&self) -> StageId {
+        StageId::with_value::<Self, _>((self.from.clone(), self.to.clone()))
+    }
+}
+
+/// Condition for [`App::set_stage_run_condition`], so a stage only runs while the app is in `value`.
+pub fn in_state<S: States>(value: S) -> impl Fn(&limnus_system_state::State) -> bool {
+    move |state| {
+        state
+            .resources()
+            .get::<State<S>>()
+            .is_some_and(|current| current.current == value)
+    }
+}
+
+fn run_stage_if_present(
+    stages: &Stages,
+    stage_id: &StageId,
+    state: &mut limnus_system_state::State,
+) {
+    if let Some(stage) = stages.get_by_stage_id(stage_id) {
+        stage.run(state);
+    }
+}
+
+/// Applies the transition requested in [`NextState`]. Requesting the current state does nothing.
+#[derive(Debug)]
+pub struct StateTransitionScheduler<S: States> {
+    phantom: PhantomData<S>,
+}
+
+impl<S: States> Default for StateTransitionScheduler<S> {
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: States> Scheduler for StateTransitionScheduler<S> {
+    fn schedule(&self, stages: &Stages, state: &mut limnus_system_state::State) {
+        let Some(next) = state
+            .resources_mut()
+            .get_mut::<NextState<S>>()
+            .and_then(|next_state| next_state.next.take())
+        else {
+            return;
+        };
+
+        let current = state.resources().fetch::<State<S>>().current.clone();
+        if current == next {
+            return;
+        }
+
+        debug!(from=?current, to=?next, "state transition");
+
+        run_stage_if_present(stages, &OnExit(current.clone()).stage_id(), state);
+
+        state.resources_mut().fetch_mut::<State<S>>().current = next.clone();
+
+        let transition = OnTransition {
+            from: current,
+            to: next.clone(),
+        };
+        run_stage_if_present(stages, &transition.stage_id(), state);
+
+        run_stage_if_present(stages, &OnEnter(next).stage_id(), state);
+    }
+}
+
+/// Runs [`OnEnter`] for the initial state, once, after the startup schedulers that were added
+/// before [`AppStatePlugin`].
+#[derive(Debug)]
+pub struct EnterInitialStateScheduler<S: States> {
+    phantom: PhantomData<S>,
+}
+
+impl<S: States> Default for EnterInitialStateScheduler<S> {
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: States> Scheduler for EnterInitialStateScheduler<S> {
+    fn schedule(&self, stages: &Stages, state: &mut limnus_system_state::State) {
+        let initial = state.resources().fetch::<State<S>>().current.clone();
+        debug!(state=?initial, "entering initial state");
+        run_stage_if_present(stages, &OnEnter(initial).stage_id(), state);
+    }
+}
+
+/// Inserts [`State`] and [`NextState`] for `S` and applies the transitions.
+///
+/// Add one plugin for each independent set of states.
+pub struct AppStatePlugin<S: States> {
+    initial: S,
+}
+
+impl<S: States> AppStatePlugin<S> {
+    #[must_use]
+    pub const fn new(initial: S) -> Self {
+        Self { initial }
+    }
+}
+
+impl<S: States> Plugin for AppStatePlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(State::new(self.initial.clone()));
+        app.insert_resource(NextState::<S>::new());
+
+        app.add_startup_scheduler(EnterInitialStateScheduler::<S>::default());
+
+        // Transitions of all state types run before the other schedulers, in the order the plugins were added.
+        let mut names = app
+            .get_resource_ref::<StateTransitionSchedulerNames>()
+            .map(|existing| existing.names.clone())
+            .unwrap_or_default();
+        let scheduler = StateTransitionScheduler::<S>::default();
+        let name = scheduler.name();
+        app.add_scheduler_after_last(|existing| names.contains(&existing.name()), scheduler);
+        names.push(name);
+        app.insert_resource(StateTransitionSchedulerNames { names });
+    }
+}
+
+/// The names of the added [`StateTransitionScheduler`]s, to keep them in registration order.
+#[derive(Debug, Resource)]
+struct StateTransitionSchedulerNames {
+    names: Vec<&'static str>,
+}
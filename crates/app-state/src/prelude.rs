@@ -0,0 +1,7 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+pub use crate::{
+    AppStatePlugin, NextState, OnEnter, OnExit, OnTransition, State, States, in_state,
+};
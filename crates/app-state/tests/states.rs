@@ -0,0 +1,208 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+#[path = "../../app/tests/common/mod.rs"]
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_app_state::prelude::*;
+use limnus_default_stages::Update;
+use limnus_macros::Resource;
+use limnus_resource::Resource;
+use limnus_system_params::ReM;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Paused,
+}
+
+impl States for GameState {}
+
+#[derive(Debug, Default, Resource)]
+struct Log {
+    entries: Vec<&'static str>,
+}
+
+fn enter_menu(mut log: ReM<Log>) {
+    log.entries.push("enter menu");
+}
+
+fn exit_menu(mut log: ReM<Log>) {
+    log.entries.push("exit menu");
+}
+
+fn menu_to_playing(mut log: ReM<Log>) {
+    log.entries.push("menu to playing");
+}
+
+fn enter_playing(mut log: ReM<Log>) {
+    log.entries.push("enter playing");
+}
+
+fn enter_paused(mut log: ReM<Log>) {
+    log.entries.push("enter paused");
+}
+
+fn update(mut log: ReM<Log>) {
+    log.entries.push("update");
+}
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Log::default());
+    app.add_plugins(AppStatePlugin::new(GameState::Menu));
+
+    app.add_system(OnEnter(GameState::Menu), enter_menu);
+    app.add_system(OnExit(GameState::Menu), exit_menu);
+    app.add_system(
+        OnTransition {
+            from: GameState::Menu,
+            to: GameState::Playing,
+        },
+        menu_to_playing,
+    );
+    app.add_system(OnEnter(GameState::Playing), enter_playing);
+    app.add_system(OnEnter(GameState::Paused), enter_paused);
+    app.add_system(Update, update);
+    app
+}
+
+fn take_log(app: &mut App) -> Vec<&'static str> {
+    std::mem::take(&mut app.resource_mut::<Log>().entries)
+}
+
+#[test]
+fn enters_initial_state_on_startup() {
+    let mut app = create_app();
+
+    app.update();
+
+    assert_eq!(take_log(&mut app), ["enter menu", "update"]);
+    assert_eq!(app.resource::<State<GameState>>().get(), &GameState::Menu);
+}
+
+#[test]
+fn transition_is_applied_before_update() {
+    let mut app = create_app();
+    app.update();
+    take_log(&mut app);
+
+    app.resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+
+    assert_eq!(
+        take_log(&mut app),
+        ["exit menu", "menu to playing", "enter playing", "update"]
+    );
+    assert_eq!(
+        app.resource::<State<GameState>>().get(),
+        &GameState::Playing
+    );
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["update"]);
+}
+
+#[test]
+fn setting_current_state_does_nothing() {
+    let mut app = create_app();
+    app.update();
+    take_log(&mut app);
+
+    app.resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+
+    assert_eq!(take_log(&mut app), ["update"]);
+    assert!(app.resource::<NextState<GameState>>().get().is_none());
+}
+
+#[test]
+fn stage_only_runs_in_state() {
+    let mut app = create_app();
+    app.set_stage_run_condition(Update, in_state(GameState::Playing));
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["enter menu"]);
+
+    app.resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+    assert_eq!(
+        take_log(&mut app),
+        ["exit menu", "menu to playing", "enter playing", "update"]
+    );
+
+    app.resource_mut::<NextState<GameState>>()
+        .set(GameState::Paused);
+    app.update();
+    assert_eq!(take_log(&mut app), ["enter paused"]);
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Level {
+    One,
+    Two,
+}
+
+impl std::hash::Hash for Level {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {
+        // Every level hashes the same, so the stages are only told apart by equality
+    }
+}
+
+impl States for Level {}
+
+fn enter_one(mut log: ReM<Log>) {
+    log.entries.push("enter one");
+}
+
+fn enter_two(mut log: ReM<Log>) {
+    log.entries.push("enter two");
+}
+
+#[test]
+fn states_with_the_same_hash_get_their_own_stages() {
+    let mut app = App::new();
+    app.insert_resource(Log::default());
+    app.add_plugins(AppStatePlugin::new(Level::One));
+    app.add_system(OnEnter(Level::One), enter_one);
+    app.add_system(OnEnter(Level::Two), enter_two);
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["enter one"]);
+
+    app.resource_mut::<NextState<Level>>().set(Level::Two);
+    app.update();
+    assert_eq!(take_log(&mut app), ["enter two"]);
+}
+
+#[test]
+fn transitions_run_in_the_order_the_state_plugins_were_added() {
+    let mut app = create_app();
+    app.add_plugins(AppStatePlugin::new(Level::One));
+    app.add_system(OnEnter(Level::Two), enter_two);
+    app.update();
+    take_log(&mut app);
+
+    app.resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+    app.resource_mut::<NextState<Level>>().set(Level::Two);
+    app.update();
+
+    assert_eq!(
+        take_log(&mut app),
+        [
+            "exit menu",
+            "menu to playing",
+            "enter playing",
+            "enter two",
+            "update"
+        ]
+    );
+}
@@ -2,41 +2,153 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
+use crate::commands::{PendingSystemChanges, SystemChange};
+use crate::plan::SchedulePlan;
 use limnus_local_resource::{LocalResource, LocalResourceStorage};
 use limnus_message::{Message, MessageId, MessageStorage, Messages, MessagesIterator};
 use limnus_resource::prelude::*;
-use limnus_scheduler::Scheduler;
+use limnus_scheduler::{ScheduleError, Scheduler};
 use limnus_scheduler_runner::Runner;
-use limnus_stage::{Stage, StageTag, Stages};
-use limnus_system::{IntoSystem, SystemParam};
+use limnus_stage::{ExecutorKind, Stage, StageError, StageId, StageTag, Stages, SystemHandle};
+use limnus_system::{
+    IntoSystem, IntoSystemConfig, MissingParamPolicy, MissingParams, OneShotSystems,
+    RunSystemError, SystemErrorHandler, SystemId, run_system,
+};
 use limnus_system_state::State;
-use std::any::type_name;
-use tracing::{debug, info};
+use std::any::{TypeId, type_name};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use tracing::{debug, info, warn};
 
 type AppRunner = dyn FnOnce(App) -> AppReturnValue;
 
 pub enum AppPhase {
     WaitingForPlugins,
     Running,
+    /// [`App::shutdown`] has been called, holds the value it returned.
+    ShutDown(AppReturnValue),
 }
 
 pub struct App {
     app_runner: Option<Box<AppRunner>>,
     schedulers_runner: Runner,
     plugins: Vec<Box<dyn Plugin>>,
+    plugin_ids: HashSet<TypeId>,
     state: State,
     phase: AppPhase,
     stages: Stages,
 }
 
 impl App {
-    pub(crate) fn internal_add_plugin(&mut self, boxed_plugin: Box<dyn Plugin>) {
-        boxed_plugin.build(self);
-        debug!(plugin=?boxed_plugin, "Added");
-        self.plugins.push(boxed_plugin);
+    /// Validates the plugins, and then builds them in order.
+    ///
+    /// Duplicates and plugin dependencies are checked for the whole collection before any plugin is built,
+    /// so a failure leaves the app as it was. Resource dependencies are checked just before each plugin is built,
+    /// since an earlier plugin in the collection can insert them.
+    pub(crate) fn internal_add_plugins(
+        &mut self,
+        plugins: Vec<Box<dyn Plugin>>,
+    ) -> Result<(), PluginError> {
+        self.validate_plugins(&plugins)?;
+
+        for boxed_plugin in plugins {
+            self.validate_resources(boxed_plugin.as_ref())?;
+            self.plugin_ids.insert(boxed_plugin.plugin_type_id());
+            self.plugin_ids.extend(boxed_plugin.replaces());
+            boxed_plugin.build(self);
+            debug!(plugin=?boxed_plugin, "Added");
+            self.plugins.push(boxed_plugin);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that no plugin has been added before, and that the plugins each plugin depends on
+    /// have been added before it, either earlier in `plugins` or to the app.
+    ///
+    /// A dependency that is added later in `plugins` is reported as mis-ordered instead of missing.
+    fn validate_plugins(&self, plugins: &[Box<dyn Plugin>]) -> Result<(), PluginError> {
+        let provided_ids = |plugin: &dyn Plugin| {
+            let mut type_ids = plugin.replaces();
+            type_ids.push(plugin.plugin_type_id());
+            type_ids
+        };
+        let mut added = self.plugin_ids.clone();
+
+        for (index, plugin) in plugins.iter().enumerate() {
+            if added.contains(&plugin.plugin_type_id()) {
+                return Err(PluginError::Duplicate {
+                    plugin: plugin.type_name(),
+                });
+            }
+
+            for dependency in plugin.dependencies() {
+                let PluginDependency::Plugin { type_id, type_name } = dependency else {
+                    continue;
+                };
+                if added.contains(&type_id) {
+                    continue;
+                }
+                let added_later = plugins[index + 1..]
+                    .iter()
+                    .any(|later| provided_ids(later.as_ref()).contains(&type_id));
+                return Err(if added_later {
+                    PluginError::MisorderedPlugin {
+                        plugin: plugin.type_name(),
+                        dependency: type_name,
+                    }
+                } else {
+                    PluginError::MissingPlugin {
+                        plugin: plugin.type_name(),
+                        dependency: type_name,
+                    }
+                });
+            }
+
+            added.extend(provided_ids(plugin.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    fn validate_resources(&self, plugin: &dyn Plugin) -> Result<(), PluginError> {
+        for dependency in plugin.dependencies() {
+            match dependency {
+                PluginDependency::Plugin { .. } => {}
+                PluginDependency::Resource {
+                    type_name,
+                    is_present,
+                } => {
+                    if !is_present(self) {
+                        return Err(PluginError::MissingResource {
+                            plugin: plugin.type_name(),
+                            resource: type_name,
+                        });
+                    }
+                }
+                PluginDependency::LocalResource {
+                    type_name,
+                    is_present,
+                } => {
+                    if !is_present(self) {
+                        return Err(PluginError::MissingLocalResource {
+                            plugin: plugin.type_name(),
+                            resource: type_name,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
     }
 
+    /// Does nothing after [`Self::shutdown`] has been called.
     pub fn update(&mut self) {
+        if matches!(self.phase, AppPhase::ShutDown(_)) {
+            return;
+        }
+
         if matches!(self.phase, AppPhase::WaitingForPlugins) {
             let mut all_are_ready = true;
 
@@ -61,10 +173,109 @@ impl App {
             self.plugins = plugins;
 
             self.phase = AppPhase::Running;
+
+            if let Err(err) = self.validate_schedulers() {
+                panic!("{err}");
+            }
+            self.sort_stages_or_panic();
+
+            debug!("running startup schedulers");
+            self.schedulers_runner
+                .run_startup_schedulers(&self.stages, &mut self.state);
         }
 
+        self.sort_stages_or_panic();
+
         self.schedulers_runner
             .run_schedulers(&self.stages, &mut self.state);
+
+        self.apply_system_changes();
+    }
+
+    /// Applies the changes to the systems that were recorded by `Commands` while the schedulers ran,
+    /// since the stages can not be changed while they are running.
+    fn apply_system_changes(&mut self) {
+        let Some(pending) = self.state.resource_mut::<PendingSystemChanges>() else {
+            return;
+        };
+        for change in pending.take() {
+            match change {
+                SystemChange::Add {
+                    handle,
+                    stage_name,
+                    created_on_demand,
+                    config,
+                } => {
+                    let stage = if created_on_demand {
+                        Some(
+                            self.stages
+                                .get_or_insert_mut(handle.stage.clone(), stage_name),
+                        )
+                    } else {
+                        self.stages.get_mut_by_stage_id(&handle.stage)
+                    };
+                    match stage {
+                        Some(stage) => {
+                            stage.insert_system(handle.key, config);
+                        }
+                        None => warn!(
+                            stage = stage_name,
+                            system = config.system().name(),
+                            "could not find stage, system was not added"
+                        ),
+                    }
+                }
+                SystemChange::Remove(handle) => {
+                    self.stages.remove_system(&handle);
+                }
+                SystemChange::SetEnabled(handle, enabled) => {
+                    self.stages.set_system_enabled(&handle, enabled);
+                }
+            }
+        }
+    }
+
+    fn sort_stages_or_panic(&mut self) {
+        if let Err(err) = self.sort_stages() {
+            panic!("{err}");
+        }
+    }
+
+    /// Runs the shutdown schedulers, lets the plugins clean up in reverse order and
+    /// drops all resources (see [`State::clear`] for the order).
+    ///
+    /// Returns the value of [`ApplicationExit`], or `AppReturnValue::Value(0)` if it was never inserted.
+    /// Only the first call shuts down the app, later calls return the same value.
+    pub fn shutdown(&mut self) -> AppReturnValue {
+        match self.phase {
+            AppPhase::ShutDown(return_value) => return return_value,
+            AppPhase::Running => {
+                debug!("running shutdown schedulers");
+                self.schedulers_runner
+                    .run_shutdown_schedulers(&self.stages, &mut self.state);
+            }
+            AppPhase::WaitingForPlugins => {
+                debug!("plugins were never ready, skipping shutdown schedulers");
+            }
+        }
+
+        let return_value = self
+            .get_resource_ref::<ApplicationExit>()
+            .map_or(AppReturnValue::Value(0), |exit| exit.value);
+
+        let plugins = std::mem::take(&mut self.plugins);
+        for plugin in plugins.iter().rev() {
+            debug!(plugin=?plugin, "cleanup");
+            plugin.cleanup(self);
+        }
+        self.plugins = plugins;
+
+        self.state.clear();
+
+        info!(?return_value, "app has shut down");
+        self.phase = AppPhase::ShutDown(return_value);
+
+        return_value
     }
 }
 
@@ -91,6 +302,7 @@ impl App {
             app_runner: None,
             state: State::new(),
             plugins: Vec::default(),
+            plugin_ids: HashSet::default(),
             phase: AppPhase::WaitingForPlugins,
             schedulers_runner: Runner::new(),
             stages: Stages::new(),
@@ -103,6 +315,7 @@ impl App {
             app_runner: None,
             state: State::new(),
             plugins: Vec::default(),
+            plugin_ids: HashSet::default(),
             phase: AppPhase::WaitingForPlugins,
             schedulers_runner: Runner::new(),
             stages: Stages::new(),
@@ -120,11 +333,39 @@ impl App {
         runner.unwrap()(app)
     }
 
+    /// # Panics
+    /// If a plugin is added twice, or if a dependency of a plugin has not been added before it.
+    /// See [`Self::try_add_plugins`].
     pub fn add_plugins<P: PluginCollection>(&mut self, collection: P) -> &mut Self {
-        collection.attach_to_app(self);
+        if let Err(err) = self.try_add_plugins(collection) {
+            panic!("{err}");
+        }
         self
     }
 
+    /// Adds and builds the plugins in the order they appear in the collection.
+    ///
+    /// No plugin is built if a plugin is added twice, or if a plugin that another plugin depends on is missing.
+    ///
+    /// # Errors
+    /// If a plugin is added twice, or if a dependency declared in [`Plugin::dependencies`]
+    /// has not been added before the plugin that needs it.
+    pub fn try_add_plugins<P: PluginCollection>(
+        &mut self,
+        collection: P,
+    ) -> Result<&mut Self, PluginError> {
+        self.internal_add_plugins(collection.into_plugins())?;
+        Ok(self)
+    }
+
+    /// Also true if `P` was replaced by a plugin that has been added, see [`PluginGroupBuilder::replace`].
+    ///
+    /// [`PluginGroupBuilder::replace`]: crate::plugin_group::PluginGroupBuilder::replace
+    #[must_use]
+    pub fn has_plugin<P: Plugin>(&self) -> bool {
+        self.plugin_ids.contains(&TypeId::of::<P>())
+    }
+
     pub fn add_stage<S>(&mut self)
     where
         S: StageTag,
@@ -133,18 +374,208 @@ impl App {
         self.stages.add::<S>(stage);
     }
 
+    /// Adds the stage `S` and runs it right after the stage `After`, in the scheduler that runs `After`.
+    ///
+    /// # Panics
+    /// If no scheduler has `After` in its editable stage list, see `Scheduler::stage_list`.
+    pub fn add_stage_after<After: StageTag, S: StageTag>(&mut self) {
+        let inserted = self
+            .schedulers_runner
+            .stage_list_containing_mut(&StageId::of::<After>())
+            .is_some_and(|list| list.insert_after::<After, S>());
+        assert!(
+            inserted,
+            "no scheduler runs stage `{}`, could not add `{}` after it",
+            type_name::<After>(),
+            type_name::<S>()
+        );
+        self.add_stage::<S>();
+    }
+
+    /// Adds the stage `S` and runs it right before the stage `Before`, in the scheduler that runs `Before`.
+    ///
     /// # Panics
-    /// a `Stage` for the type parameter `S` must exist
-    pub fn add_system<F, Params, S>(&mut self, _stage_tag: S, system: F)
+    /// If no scheduler has `Before` in its editable stage list, see `Scheduler::stage_list`.
+    pub fn add_stage_before<Before: StageTag, S: StageTag>(&mut self) {
+        let inserted = self
+            .schedulers_runner
+            .stage_list_containing_mut(&StageId::of::<Before>())
+            .is_some_and(|list| list.insert_before::<Before, S>());
+        assert!(
+            inserted,
+            "no scheduler runs stage `{}`, could not add `{}` before it",
+            type_name::<Before>(),
+            type_name::<S>()
+        );
+        self.add_stage::<S>();
+    }
+
+    /// Checks that the stages listed by the schedulers have been added, and that no stage is run
+    /// by more than one scheduler. Called by [`Self::update`] at startup, which panics on errors.
+    ///
+    /// # Errors
+    /// The first [`ScheduleError`] that is encountered.
+    pub fn validate_schedulers(&self) -> Result<(), ScheduleError> {
+        self.schedulers_runner.validate(&self.stages)
+    }
+
+    /// Adds the system to the stage, also while the app is running.
+    ///
+    /// Returns the handle used to remove, enable or disable the system later.
+    /// Systems can do the same with `Commands::add_system`.
+    ///
+    /// # Panics
+    /// a `Stage` for the `stage_tag` must exist, unless the tag is [`StageTag::CREATED_ON_DEMAND`].
+    /// See [`Self::try_add_system`].
+    pub fn add_system<F, Params, S>(&mut self, stage_tag: S, system: F) -> SystemHandle
     where
-        F: IntoSystem<Params>,
-        Params: SystemParam,
+        F: IntoSystemConfig<Params>,
+        S: StageTag,
+    {
+        self.try_add_system(stage_tag, system)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Adds the system to the stage, like [`Self::add_system`].
+    ///
+    /// # Errors
+    /// If there is no `Stage` for the `stage_tag`, unless the tag is [`StageTag::CREATED_ON_DEMAND`].
+    pub fn try_add_system<F, Params, S>(
+        &mut self,
+        stage_tag: S,
+        system: F,
+    ) -> Result<SystemHandle, AddSystemError>
+    where
+        F: IntoSystemConfig<Params>,
         S: StageTag,
     {
+        let stage_id = stage_tag.stage_id();
+        let stage = if S::CREATED_ON_DEMAND {
+            self.stages
+                .get_or_insert_mut(stage_id.clone(), type_name::<S>())
+        } else {
+            self.stages
+                .get_mut_by_stage_id(&stage_id)
+                .ok_or(AddSystemError::MissingStage {
+                    stage: type_name::<S>(),
+                })?
+        };
+        let key = stage.add_system(system);
+        Ok(SystemHandle {
+            stage: stage_id,
+            key,
+        })
+    }
+
+    /// Removes a system that was added with [`Self::add_system`].
+    ///
+    /// Returns `false` if the system has already been removed.
+    pub fn remove_system(&mut self, handle: &SystemHandle) -> bool {
+        self.stages.remove_system(handle)
+    }
+
+    /// A disabled system stays in its stage, but is not run until it is enabled again.
+    ///
+    /// Returns `false` if the system has been removed.
+    pub fn set_system_enabled(&mut self, handle: &SystemHandle, enabled: bool) -> bool {
+        self.stages.set_system_enabled(handle, enabled)
+    }
+
+    /// `None` if the system has been removed.
+    #[must_use]
+    pub fn is_system_enabled(&self, handle: &SystemHandle) -> Option<bool> {
+        self.stages
+            .get_by_stage_id(&handle.stage)
+            .and_then(|stage| stage.is_system_enabled(handle.key))
+    }
+
+    /// The systems in the stage are only run when `condition` returns `true`.
+    ///
+    /// # Panics
+    /// a `Stage` for the `stage_tag` must exist, unless the tag is [`StageTag::CREATED_ON_DEMAND`]
+    pub fn set_stage_run_condition<S: StageTag>(
+        &mut self,
+        stage_tag: S,
+        condition: impl Fn(&State) -> bool + 'static,
+    ) {
+        self.stage_mut(&stage_tag).set_run_condition(condition);
+    }
+
+    /// Selects how the systems in the stage are run, for example in parallel.
+    ///
+    /// # Panics
+    /// a `Stage` for the `stage_tag` must exist, unless the tag is [`StageTag::CREATED_ON_DEMAND`]
+    pub fn set_stage_executor<S: StageTag>(&mut self, stage_tag: S, executor: ExecutorKind) {
+        self.stage_mut(&stage_tag).set_executor(executor);
+    }
+
+    /// Selects what happens when a system is not run because one of its params is missing.
+    /// The default is [`MissingParamPolicy::WarnOnce`].
+    pub fn set_missing_param_policy(&mut self, policy: MissingParamPolicy) {
+        self.insert_resource(MissingParams::new(policy));
+    }
+
+    /// Registers a system that is only run when asked to, with [`Self::run_system`] or `Commands::run_system`.
+    pub fn register_system<F, Params>(&mut self, system: F) -> SystemId
+    where
+        F: IntoSystem<Params>,
+        F::System: Send + Sync,
+    {
+        if !self.has_resource::<OneShotSystems>() {
+            self.insert_resource(OneShotSystems::default());
+        }
+        self.resource_mut::<OneShotSystems>().register(system)
+    }
+
+    /// Runs the one-shot system right away, and then applies the commands that it recorded.
+    ///
+    /// # Errors
+    /// If there is no system with the id, or if the system returned an error.
+    pub fn run_system(&mut self, id: SystemId) -> Result<(), RunSystemError> {
+        let result = run_system(&mut self.state, id);
+        self.state.apply_commands();
+        self.apply_system_changes();
+        result
+    }
+
+    /// Selects what happens with the errors returned by systems. The default is [`SystemErrorHandler::Log`].
+    pub fn set_system_error_handler(&mut self, handler: SystemErrorHandler) {
+        self.insert_resource(handler);
+    }
+
+    /// The schedulers, stages and systems in the order they run, for exporting with
+    /// [`SchedulePlan::to_json`] or [`SchedulePlan::to_dot`]. Sorts the stages first.
+    ///
+    /// # Errors
+    /// If the stages could not be sorted, see [`Self::sort_stages`].
+    pub fn schedule_plan(&mut self) -> Result<SchedulePlan, StageError> {
+        self.sort_stages()?;
+        Ok(SchedulePlan::new(
+            self.schedulers_runner.startup_schedulers(),
+            self.schedulers_runner.schedulers(),
+            self.schedulers_runner.shutdown_schedulers(),
+            &self.stages,
+        ))
+    }
+
+    /// Orders the systems in all stages according to their `before` and `after` constraints.
+    ///
+    /// Called by [`Self::update`], which panics on errors. Call it directly to check the constraints up front.
+    ///
+    /// # Errors
+    /// If a constraint refers to an unknown label, or if the constraints in a stage form a cycle.
+    pub fn sort_stages(&mut self) -> Result<(), StageError> {
+        self.stages.sort()
+    }
+
+    fn stage_mut<S: StageTag>(&mut self, stage_tag: &S) -> &mut Stage {
+        let stage_id = stage_tag.stage_id();
+        if S::CREATED_ON_DEMAND {
+            return self.stages.get_or_insert_mut(stage_id, type_name::<S>());
+        }
         self.stages
-            .get_mut::<S>()
-            .expect("could not find stage")
-            .add_system(system);
+            .get_mut_by_stage_id(&stage_id)
+            .unwrap_or_else(|| panic!("could not find stage `{}`", type_name::<S>()))
     }
 
     pub fn add_scheduler<T>(&mut self, scheduler: T)
@@ -154,6 +585,43 @@ impl App {
         self.schedulers_runner.add_scheduler(scheduler);
     }
 
+    /// The scheduler is run before all schedulers that have already been added.
+    pub fn add_scheduler_first<T>(&mut self, scheduler: T)
+    where
+        T: Scheduler,
+    {
+        self.schedulers_runner.add_scheduler_first(scheduler);
+    }
+
+    /// The scheduler is run right after the last already added scheduler that matches `after`,
+    /// or before all of them if none matches.
+    pub fn add_scheduler_after_last<T>(
+        &mut self,
+        after: impl Fn(&dyn Scheduler) -> bool,
+        scheduler: T,
+    ) where
+        T: Scheduler,
+    {
+        self.schedulers_runner
+            .add_scheduler_after_last(after, scheduler);
+    }
+
+    /// The scheduler is run once, when all plugins are initialized, before the first regular schedulers.
+    pub fn add_startup_scheduler<T>(&mut self, scheduler: T)
+    where
+        T: Scheduler,
+    {
+        self.schedulers_runner.add_startup_scheduler(scheduler);
+    }
+
+    /// The scheduler is run once by [`Self::shutdown`], instead of on every update.
+    pub fn add_shutdown_scheduler<T>(&mut self, scheduler: T)
+    where
+        T: Scheduler,
+    {
+        self.schedulers_runner.add_shutdown_scheduler(scheduler);
+    }
+
     /// The function supplied by `app_runner` can in some scenarios never return.
     pub fn set_runner(
         &mut self,
@@ -250,81 +718,179 @@ impl App {
     }
 
     #[must_use]
-    pub fn iter_current<M: Message>(&self) -> MessagesIterator<M> {
+    pub fn iter_current<M: Message>(&self) -> MessagesIterator<'_, M> {
         self.state.messages().get::<M>().unwrap().iter_current()
     }
 
     #[must_use]
-    pub fn iter_previous<M: Message>(&self) -> MessagesIterator<M> {
+    pub fn iter_previous<M: Message>(&self) -> MessagesIterator<'_, M> {
         self.state.messages().get::<M>().unwrap().iter_previous()
     }
 }
 
+/// Anything that can be passed to [`App::add_plugins`]: a single plugin, a [`PluginGroupBuilder`],
+/// a `Vec` of boxed plugins, or a tuple of up to sixteen collections. Tuples can be nested,
+/// so there is no limit on the number of plugins.
+///
+/// [`PluginGroupBuilder`]: crate::plugin_group::PluginGroupBuilder
 pub trait PluginCollection {
-    fn attach_to_app(self, app: &mut App);
+    fn into_plugins(self) -> Vec<Box<dyn Plugin>>;
 }
 
 impl<T: Plugin> PluginCollection for T {
-    fn attach_to_app(self, app: &mut App) {
-        let boxed = Box::new(self);
-        app.internal_add_plugin(boxed);
+    fn into_plugins(self) -> Vec<Box<dyn Plugin>> {
+        vec![Box::new(self)]
     }
 }
 
-impl<T1: Plugin, T2: Plugin> PluginCollection for (T1, T2) {
-    fn attach_to_app(self, app: &mut App) {
-        let boxed_plugin1 = Box::new(self.0);
-        let boxed_plugin2 = Box::new(self.1);
-
-        app.internal_add_plugin(boxed_plugin1);
-        app.internal_add_plugin(boxed_plugin2);
+impl PluginCollection for Vec<Box<dyn Plugin>> {
+    fn into_plugins(self) -> Vec<Box<dyn Plugin>> {
+        self
     }
 }
 
-impl<T1: Plugin, T2: Plugin, T3: Plugin> PluginCollection for (T1, T2, T3) {
-    fn attach_to_app(self, app: &mut App) {
-        let boxed_plugin1 = Box::new(self.0);
-        let boxed_plugin2 = Box::new(self.1);
-        let boxed_plugin3 = Box::new(self.2);
+macro_rules! impl_plugin_collection_for_tuple {
+    ($($collection:ident),+) => {
+        impl<$($collection: PluginCollection),+> PluginCollection for ($($collection,)+) {
+            #[allow(non_snake_case)]
+            fn into_plugins(self) -> Vec<Box<dyn Plugin>> {
+                let ($($collection,)+) = self;
+                let mut plugins = Vec::new();
+                $(plugins.extend($collection.into_plugins());)+
+                plugins
+            }
+        }
+    };
+}
+
+impl_plugin_collection_for_tuple!(T1, T2);
+impl_plugin_collection_for_tuple!(T1, T2, T3);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6, T7);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_plugin_collection_for_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_plugin_collection_for_tuple!(
+    T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15
+);
+impl_plugin_collection_for_tuple!(
+    T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16
+);
+
+/// Something that must be added to the [`App`] before a plugin is built.
+#[derive(Debug, Clone, Copy)]
+pub enum PluginDependency {
+    Plugin {
+        type_id: TypeId,
+        type_name: &'static str,
+    },
+    Resource {
+        type_name: &'static str,
+        is_present: fn(&App) -> bool,
+    },
+    LocalResource {
+        type_name: &'static str,
+        is_present: fn(&App) -> bool,
+    },
+}
+
+impl PluginDependency {
+    #[must_use]
+    pub fn plugin<P: Plugin>() -> Self {
+        Self::Plugin {
+            type_id: TypeId::of::<P>(),
+            type_name: type_name::<P>(),
+        }
+    }
+
+    #[must_use]
+    pub fn resource<R: Resource>() -> Self {
+        Self::Resource {
+            type_name: type_name::<R>(),
+            is_present: |app| app.has_resource::<R>(),
+        }
+    }
 
-        app.internal_add_plugin(boxed_plugin1);
-        app.internal_add_plugin(boxed_plugin2);
-        app.internal_add_plugin(boxed_plugin3);
+    #[must_use]
+    pub fn local_resource<R: LocalResource>() -> Self {
+        Self::LocalResource {
+            type_name: type_name::<R>(),
+            is_present: |app| app.local_resources().contains::<R>(),
+        }
     }
 }
 
-impl<T1: Plugin, T2: Plugin, T3: Plugin, T4: Plugin> PluginCollection for (T1, T2, T3, T4) {
-    fn attach_to_app(self, app: &mut App) {
-        let boxed_plugin1 = Box::new(self.0);
-        let boxed_plugin2 = Box::new(self.1);
-        let boxed_plugin3 = Box::new(self.2);
-        let boxed_plugin4 = Box::new(self.3);
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PluginError {
+    Duplicate {
+        plugin: &'static str,
+    },
+    MissingPlugin {
+        plugin: &'static str,
+        dependency: &'static str,
+    },
+    MisorderedPlugin {
+        plugin: &'static str,
+        dependency: &'static str,
+    },
+    MissingResource {
+        plugin: &'static str,
+        resource: &'static str,
+    },
+    MissingLocalResource {
+        plugin: &'static str,
+        resource: &'static str,
+    },
+}
 
-        app.internal_add_plugin(boxed_plugin1);
-        app.internal_add_plugin(boxed_plugin2);
-        app.internal_add_plugin(boxed_plugin3);
-        app.internal_add_plugin(boxed_plugin4);
+impl Display for PluginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Duplicate { plugin } => write!(f, "plugin `{plugin}` has already been added"),
+            Self::MissingPlugin { plugin, dependency } => write!(
+                f,
+                "plugin `{plugin}` depends on plugin `{dependency}`, which has not been added"
+            ),
+            Self::MisorderedPlugin { plugin, dependency } => write!(
+                f,
+                "plugin `{plugin}` depends on plugin `{dependency}`, which must be added before it"
+            ),
+            Self::MissingResource { plugin, resource } => write!(
+                f,
+                "plugin `{plugin}` depends on resource `{resource}`, which has not been inserted"
+            ),
+            Self::MissingLocalResource { plugin, resource } => write!(
+                f,
+                "plugin `{plugin}` depends on local resource `{resource}`, which has not been inserted"
+            ),
+        }
     }
 }
 
-impl<T1: Plugin, T2: Plugin, T3: Plugin, T4: Plugin, T5: Plugin> PluginCollection
-    for (T1, T2, T3, T4, T5)
-{
-    fn attach_to_app(self, app: &mut App) {
-        let boxed_plugin1 = Box::new(self.0);
-        let boxed_plugin2 = Box::new(self.1);
-        let boxed_plugin3 = Box::new(self.2);
-        let boxed_plugin4 = Box::new(self.3);
-        let boxed_plugin5 = Box::new(self.4);
-
-        app.internal_add_plugin(boxed_plugin1);
-        app.internal_add_plugin(boxed_plugin2);
-        app.internal_add_plugin(boxed_plugin3);
-        app.internal_add_plugin(boxed_plugin4);
-        app.internal_add_plugin(boxed_plugin5);
+impl std::error::Error for PluginError {}
+
+/// Returned by [`App::try_add_system`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AddSystemError {
+    MissingStage { stage: &'static str },
+}
+
+impl Display for AddSystemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingStage { stage } => write!(f, "could not find stage `{stage}`"),
+        }
     }
 }
 
+impl std::error::Error for AddSystemError {}
+
 /// Plugins are not allowed to mutate themselves, just reference the app
 pub trait Plugin: 'static {
     // Send + Sync +
@@ -336,9 +902,30 @@ pub trait Plugin: 'static {
 
     fn post_initialization(&self, _app: &mut App) {}
 
+    /// Called by [`App::shutdown`] after the shutdown schedulers, in reverse plugin order,
+    /// while all resources are still present.
+    fn cleanup(&self, _app: &mut App) {}
+
+    /// Plugins and resources that must be added before this plugin is built.
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        Vec::new()
+    }
+
     fn type_name(&self) -> &'static str {
         type_name::<Self>()
     }
+
+    fn plugin_type_id(&self) -> TypeId {
+        TypeId::of::<Self>()
+    }
+
+    /// The plugin types that this plugin stands in for, so it satisfies dependencies on them.
+    /// Set by [`PluginGroupBuilder::replace`].
+    ///
+    /// [`PluginGroupBuilder::replace`]: crate::plugin_group::PluginGroupBuilder::replace
+    fn replaces(&self) -> Vec<TypeId> {
+        Vec::new()
+    }
 }
 
 impl std::fmt::Debug for dyn Plugin {
@@ -0,0 +1,181 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::app::{AppReturnValue, ApplicationExit};
+use limnus_local_resource::LocalResource;
+use limnus_message::Message;
+use limnus_resource::Resource;
+use limnus_stage::{StageTag, SystemHandle, SystemKey};
+use limnus_system::{
+    Access, IntoSystemConfig, RunSystemError, SystemConfig, SystemError, SystemId, SystemParam,
+    handle_system_error, run_system,
+};
+use limnus_system_state::{CommandQueue, State, StateCell};
+use std::any::type_name;
+use std::fmt::{Debug, Formatter};
+use std::mem::take;
+use tracing::warn;
+
+/// Records changes to the `State` that are applied at the end of the stage, after all systems in it have run.
+///
+/// Unlike `ReAll` and `LoReAll`, any number of systems can use `Commands` in the same stage.
+///
+/// ```
+/// use limnus_app::prelude::*;
+///
+/// fn quit(mut commands: Commands) {
+///     commands.request_exit(AppReturnValue::Value(0));
+/// }
+/// ```
+pub struct Commands<'a> {
+    queue: &'a CommandQueue,
+}
+
+impl Commands<'_> {
+    /// Inserts the resource, replacing any resource of the same type.
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) {
+        self.queue
+            .push(move |state| state.resources_mut().insert(resource));
+    }
+
+    pub fn remove_resource<R: Resource>(&mut self) {
+        self.queue.push(|state| {
+            state.resources_mut().remove::<R>();
+        });
+    }
+
+    /// Inserts the local resource. It must be `Send`, since the system that records the command
+    /// may not run on the main thread.
+    pub fn insert_local_resource<R: LocalResource + Send>(&mut self, resource: R) {
+        self.queue
+            .push(move |state| state.local_resources_mut().insert(resource));
+    }
+
+    /// Sends the message, registering the message type if needed.
+    pub fn send<M: Message>(&mut self, message: M) {
+        self.queue.push(move |state| {
+            state.messages_mut().send(message);
+        });
+    }
+
+    /// Inserts [`ApplicationExit`], so the app exits with `value` after the current update.
+    pub fn request_exit(&mut self, value: AppReturnValue) {
+        self.insert_resource(ApplicationExit { value });
+    }
+
+    /// Runs the one-shot system with the id when the commands are applied, see `App::register_system`.
+    ///
+    /// Errors returned by the system are passed to the `SystemErrorHandler`, with `commands` as the stage.
+    pub fn run_system(&mut self, id: SystemId) {
+        self.queue.push(move |state| match run_system(state, id) {
+            Ok(()) => {}
+            Err(RunSystemError::Failed { system, error }) => handle_system_error(
+                state,
+                SystemError {
+                    system,
+                    stage: "commands",
+                    error,
+                },
+            ),
+            Err(err @ RunSystemError::NotRegistered(_)) => warn!(%err, "could not run system"),
+        });
+    }
+
+    /// Adds the system to the stage after the current update, when all schedulers are done.
+    ///
+    /// The handle can be used right away, with [`Self::remove_system`] and [`Self::set_system_enabled`].
+    /// If the stage is missing, and the tag is not [`StageTag::CREATED_ON_DEMAND`], a warning is logged instead.
+    pub fn add_system<F, Params, S>(&mut self, stage_tag: S, system: F) -> SystemHandle
+    where
+        F: IntoSystemConfig<Params>,
+        S: StageTag,
+    {
+        let handle = SystemHandle {
+            stage: stage_tag.stage_id(),
+            key: SystemKey::unique(),
+        };
+        self.push_system_change(SystemChange::Add {
+            handle: handle.clone(),
+            stage_name: type_name::<S>(),
+            created_on_demand: S::CREATED_ON_DEMAND,
+            config: system.into_system_config(),
+        });
+        handle
+    }
+
+    /// Removes the system after the current update, when all schedulers are done.
+    pub fn remove_system(&mut self, handle: &SystemHandle) {
+        self.push_system_change(SystemChange::Remove(handle.clone()));
+    }
+
+    /// Enables or disables the system after the current update, when all schedulers are done.
+    pub fn set_system_enabled(&mut self, handle: &SystemHandle, enabled: bool) {
+        self.push_system_change(SystemChange::SetEnabled(handle.clone(), enabled));
+    }
+
+    fn push_system_change(&mut self, change: SystemChange) {
+        self.queue.push(move |state| {
+            if let Some(pending) = state.resource_mut::<PendingSystemChanges>() {
+                pending.changes.push(change);
+            } else {
+                state.resources_mut().insert(PendingSystemChanges {
+                    changes: vec![change],
+                });
+            }
+        });
+    }
+
+    /// Adds a custom command.
+    pub fn add(&mut self, command: impl FnOnce(&mut State) + Send + 'static) {
+        self.queue.push(command);
+    }
+}
+
+impl SystemParam for Commands<'static> {
+    type Item = Self;
+    type Local = ();
+
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let queue: *const CommandQueue = state.commands();
+        Some(Commands {
+            queue: unsafe { &*queue },
+        })
+    }
+
+    /// The queue is synchronized, and nothing else is touched until the commands are applied.
+    fn access(access: &mut Access) {
+        access.add_read_commands();
+    }
+}
+
+pub(crate) enum SystemChange {
+    Add {
+        handle: SystemHandle,
+        stage_name: &'static str,
+        created_on_demand: bool,
+        config: SystemConfig,
+    },
+    Remove(SystemHandle),
+    SetEnabled(SystemHandle, bool),
+}
+
+/// The changes to the systems that `App` applies after the schedulers have run.
+#[derive(Default)]
+pub(crate) struct PendingSystemChanges {
+    changes: Vec<SystemChange>,
+}
+
+impl PendingSystemChanges {
+    pub fn take(&mut self) -> Vec<SystemChange> {
+        take(&mut self.changes)
+    }
+}
+
+impl Debug for PendingSystemChanges {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PendingSystemChanges({})", self.changes.len())
+    }
+}
+
+impl Resource for PendingSystemChanges {}
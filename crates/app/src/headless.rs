@@ -0,0 +1,119 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::app::{App, AppReturnValue, ApplicationExit, Plugin};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Limits and pacing for [`headless_runner`].
+///
+/// With no limits set, the runner keeps updating until [`ApplicationExit`] is inserted.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct HeadlessRunnerSettings {
+    /// Minimum duration of a frame. The runner sleeps for the remainder if an update finishes early.
+    pub frame_budget: Option<Duration>,
+    /// Stop after this many calls to [`App::update`].
+    pub max_frames: Option<u64>,
+    /// Stop when this much wall-clock time has passed since the runner started.
+    pub max_duration: Option<Duration>,
+}
+
+impl HeadlessRunnerSettings {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            frame_budget: None,
+            max_frames: None,
+            max_duration: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_frame_budget(mut self, frame_budget: Duration) -> Self {
+        self.frame_budget = Some(frame_budget);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_frames(mut self, max_frames: u64) -> Self {
+        self.max_frames = Some(max_frames);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+}
+
+/// Updates the app in a loop without opening a window.
+///
+/// Stops as soon as [`ApplicationExit`] is inserted or one of the limits in `settings` is reached,
+/// and returns the value of [`App::shutdown`].
+pub fn headless_runner(mut app: App, settings: HeadlessRunnerSettings) -> AppReturnValue {
+    debug!(?settings, "headless-runner started!");
+
+    let started_at = Instant::now();
+    let mut frame_count: u64 = 0;
+
+    loop {
+        // Checked before the update, so that a limit of zero frames does not update at all
+        if settings
+            .max_frames
+            .is_some_and(|max_frames| frame_count >= max_frames)
+        {
+            debug!(frame_count, "max frame count reached");
+            break;
+        }
+
+        let frame_started_at = Instant::now();
+
+        app.update();
+        frame_count += 1;
+
+        if let Some(exit) = app.get_resource_ref::<ApplicationExit>() {
+            debug!(frame_count, value=?exit.value, "application exit requested");
+            break;
+        }
+
+        if settings
+            .max_duration
+            .is_some_and(|max_duration| started_at.elapsed() >= max_duration)
+        {
+            debug!(frame_count, "max duration reached");
+            break;
+        }
+
+        if let Some(frame_budget) = settings.frame_budget {
+            let frame_time = frame_started_at.elapsed();
+            if frame_time < frame_budget {
+                sleep(frame_budget - frame_time);
+            }
+        }
+    }
+
+    app.shutdown()
+}
+
+/// Sets [`headless_runner`] as the runner of the app, e.g. for tests, servers and CI machines without a display.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeadlessRunnerPlugin {
+    pub settings: HeadlessRunnerSettings,
+}
+
+impl HeadlessRunnerPlugin {
+    #[must_use]
+    pub const fn new(settings: HeadlessRunnerSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl Plugin for HeadlessRunnerPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = self.settings;
+        app.set_runner(move |app| headless_runner(app, settings));
+    }
+}
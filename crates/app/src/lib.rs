@@ -3,5 +3,9 @@
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
 mod app;
+mod commands;
+mod headless;
+mod plan;
+mod plugin_group;
 
 pub mod prelude;
@@ -0,0 +1,428 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_scheduler::Scheduler;
+use limnus_stage::{ExecutorKind, Stage, StageId, Stages};
+use limnus_system::{Access, StorageAccess};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// The execution plan of an [`crate::prelude::App`], created with [`crate::prelude::App::schedule_plan`].
+///
+/// Can be exported with [`Self::to_json`] and [`Self::to_dot`].
+#[derive(Debug, Clone)]
+pub struct SchedulePlan {
+    pub startup: Vec<SchedulerPlan>,
+    /// The schedulers that run on each update, in the order they run.
+    pub update: Vec<SchedulerPlan>,
+    pub shutdown: Vec<SchedulerPlan>,
+    /// Stages that none of the schedulers list in `Scheduler::stage_ids`, sorted by name.
+    pub unscheduled: Vec<StagePlan>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SchedulerPlan {
+    pub name: &'static str,
+    /// Empty if the scheduler does not list its stages, see `Scheduler::stage_ids`.
+    pub stages: Vec<StagePlan>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StagePlan {
+    pub name: &'static str,
+    pub executor: ExecutorKind,
+    pub has_run_condition: bool,
+    /// In the order they run.
+    pub systems: Vec<SystemPlan>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemPlan {
+    pub name: &'static str,
+    /// Disabled systems are skipped when the stage runs.
+    pub enabled: bool,
+    pub labels: Vec<&'static str>,
+    pub before: Vec<&'static str>,
+    pub after: Vec<&'static str>,
+    /// What the params read, like `resource my_game::Score` or `all messages`, sorted.
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
+    /// Positions in [`StagePlan::systems`] of the systems that must run before this one.
+    pub runs_after: Vec<usize>,
+}
+
+impl SchedulePlan {
+    pub(crate) fn new(
+        startup: &[Box<dyn Scheduler>],
+        update: &[Box<dyn Scheduler>],
+        shutdown: &[Box<dyn Scheduler>],
+        stages: &Stages,
+    ) -> Self {
+        let mut scheduled = HashSet::new();
+        let mut scheduler_plans = |schedulers: &[Box<dyn Scheduler>]| -> Vec<SchedulerPlan> {
+            schedulers
+                .iter()
+                .map(|scheduler| SchedulerPlan {
+                    name: scheduler.name(),
+                    stages: scheduler
+                        .stage_ids()
+                        .iter()
+                        .filter_map(|stage_id| {
+                            scheduled.insert(stage_id.clone());
+                            stages.get_by_stage_id(stage_id).map(StagePlan::new)
+                        })
+                        .collect(),
+                })
+                .collect()
+        };
+
+        let startup = scheduler_plans(startup);
+        let update = scheduler_plans(update);
+        let shutdown = scheduler_plans(shutdown);
+
+        let mut unscheduled: Vec<StagePlan> = stages
+            .stages
+            .iter()
+            .filter(|(stage_id, _)| !scheduled.contains::<StageId>(stage_id))
+            .map(|(_, stage)| StagePlan::new(stage))
+            .collect();
+        unscheduled.sort_by_key(|stage| stage.name);
+
+        Self {
+            startup,
+            update,
+            shutdown,
+            unscheduled,
+        }
+    }
+
+    /// The plan as pretty-printed JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let scheduler_values = |schedulers: &[SchedulerPlan]| {
+            Json::Array(
+                schedulers
+                    .iter()
+                    .map(|scheduler| {
+                        Json::Object(vec![
+                            ("name", Json::String(scheduler.name.to_string())),
+                            (
+                                "stages",
+                                Json::Array(scheduler.stages.iter().map(StagePlan::json).collect()),
+                            ),
+                        ])
+                    })
+                    .collect(),
+            )
+        };
+
+        let plan = Json::Object(vec![
+            ("startup", scheduler_values(&self.startup)),
+            ("update", scheduler_values(&self.update)),
+            ("shutdown", scheduler_values(&self.shutdown)),
+            (
+                "unscheduled",
+                Json::Array(self.unscheduled.iter().map(StagePlan::json).collect()),
+            ),
+        ]);
+
+        let mut json = String::new();
+        plan.write(&mut json, 0);
+        json.push('\n');
+        json
+    }
+
+    /// The plan as a Graphviz DOT graph, with a cluster for each scheduler and stage.
+    ///
+    /// The stages of a scheduler are chained in the order they run, and the systems in a stage
+    /// have an edge from each system that must run before them.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph schedule {\n    rankdir=LR;\n    compound=true;\n");
+
+        let groups = [
+            ("startup", &self.startup),
+            ("update", &self.update),
+            ("shutdown", &self.shutdown),
+        ];
+        for (group, schedulers) in groups {
+            for (scheduler_index, scheduler) in schedulers.iter().enumerate() {
+                let id = format!("{group}_{scheduler_index}");
+                let _ = writeln!(dot, "    subgraph \"cluster_{id}\" {{");
+                let _ = writeln!(
+                    dot,
+                    "        label={};",
+                    dot_string(&format!("{group}: {}", scheduler.name))
+                );
+                for (stage_index, stage) in scheduler.stages.iter().enumerate() {
+                    stage.write_dot(&mut dot, &format!("{id}_{stage_index}"), "        ");
+                }
+                for stage_index in 1..scheduler.stages.len() {
+                    let _ = writeln!(
+                        dot,
+                        "        \"{id}_{}\" -> \"{id}_{stage_index}\" [style=bold];",
+                        stage_index - 1
+                    );
+                }
+                dot.push_str("    }\n");
+            }
+        }
+
+        for (stage_index, stage) in self.unscheduled.iter().enumerate() {
+            stage.write_dot(&mut dot, &format!("unscheduled_{stage_index}"), "    ");
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl StagePlan {
+    fn new(stage: &Stage) -> Self {
+        let order = stage.order();
+        let position_of = |index: usize| order.iter().position(|&other| other == index);
+
+        let systems = order
+            .iter()
+            .map(|&index| {
+                let config = &stage.systems()[index];
+                let access = config.system().access();
+                let mut runs_after: Vec<usize> = stage
+                    .predecessors()
+                    .get(index)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|&predecessor| position_of(predecessor))
+                    .collect();
+                runs_after.sort_unstable();
+
+                SystemPlan {
+                    name: config.system().name(),
+                    enabled: stage
+                        .is_system_enabled(stage.system_keys()[index])
+                        .unwrap_or(true),
+                    labels: config.labels().to_vec(),
+                    before: config.runs_before().to_vec(),
+                    after: config.runs_after().to_vec(),
+                    reads: access_names(&access, false),
+                    writes: access_names(&access, true),
+                    runs_after,
+                }
+            })
+            .collect();
+
+        Self {
+            name: stage.name(),
+            executor: stage.executor(),
+            has_run_condition: stage.has_run_condition(),
+            systems,
+        }
+    }
+
+    fn json(&self) -> Json {
+        let strings = |values: &[&'static str]| {
+            Json::Array(
+                values
+                    .iter()
+                    .map(|value| Json::String((*value).to_string()))
+                    .collect(),
+            )
+        };
+        let systems = self
+            .systems
+            .iter()
+            .map(|system| {
+                Json::Object(vec![
+                    ("name", Json::String(system.name.to_string())),
+                    ("enabled", Json::Bool(system.enabled)),
+                    ("labels", strings(&system.labels)),
+                    ("before", strings(&system.before)),
+                    ("after", strings(&system.after)),
+                    (
+                        "reads",
+                        Json::Array(system.reads.iter().cloned().map(Json::String).collect()),
+                    ),
+                    (
+                        "writes",
+                        Json::Array(system.writes.iter().cloned().map(Json::String).collect()),
+                    ),
+                    (
+                        "runs_after",
+                        Json::Array(
+                            system
+                                .runs_after
+                                .iter()
+                                .map(|position| Json::Number(*position))
+                                .collect(),
+                        ),
+                    ),
+                ])
+            })
+            .collect();
+
+        Json::Object(vec![
+            ("name", Json::String(self.name.to_string())),
+            ("executor", Json::String(format!("{:?}", self.executor))),
+            ("has_run_condition", Json::Bool(self.has_run_condition)),
+            ("systems", Json::Array(systems)),
+        ])
+    }
+
+    fn write_dot(&self, dot: &mut String, id: &str, indent: &str) {
+        let _ = writeln!(dot, "{indent}subgraph \"cluster_{id}\" {{");
+        let mut label = format!("{} ({:?})", self.name, self.executor);
+        if self.has_run_condition {
+            label.push_str(" if condition");
+        }
+        let _ = writeln!(dot, "{indent}    label={};", dot_string(&label));
+        let _ = writeln!(dot, "{indent}    \"{id}\" [shape=point];");
+        for (position, system) in self.systems.iter().enumerate() {
+            let mut label = system.name.to_string();
+            for read in &system.reads {
+                let _ = write!(label, "\nreads {read}");
+            }
+            for write in &system.writes {
+                let _ = write!(label, "\nwrites {write}");
+            }
+            let style = if system.enabled { "" } else { "style=dashed, " };
+            let _ = writeln!(
+                dot,
+                "{indent}    \"{id}_{position}\" [shape=box, {style}label={}];",
+                dot_string(&label)
+            );
+            if system.runs_after.is_empty() {
+                let _ = writeln!(dot, "{indent}    \"{id}\" -> \"{id}_{position}\";");
+            }
+            for predecessor in &system.runs_after {
+                let _ = writeln!(
+                    dot,
+                    "{indent}    \"{id}_{predecessor}\" -> \"{id}_{position}\";"
+                );
+            }
+        }
+        let _ = writeln!(dot, "{indent}}}");
+    }
+}
+
+/// Sorted names of what the access reads (or writes), with the storage in front.
+fn access_names(access: &Access, writes: bool) -> Vec<String> {
+    let storages: [(&str, &StorageAccess); 3] = [
+        ("resource", access.resources()),
+        ("local resource", access.local_resources()),
+        ("message", access.messages()),
+    ];
+
+    let mut names = Vec::new();
+    for (storage, storage_access) in storages {
+        let all = if writes {
+            storage_access.writes_all()
+        } else {
+            storage_access.reads_all() && !storage_access.writes_all()
+        };
+        if all {
+            names.push(format!("all {storage}s"));
+        }
+        let type_names = if writes {
+            storage_access.write_names()
+        } else {
+            storage_access.read_names()
+        };
+        names.extend(
+            type_names
+                .into_iter()
+                .map(|type_name| format!("{storage} {type_name}")),
+        );
+    }
+    if !writes && access.reads_commands() {
+        names.push("command queue".to_string());
+    }
+    names.sort();
+    names
+}
+
+fn dot_string(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// Just enough JSON to write the plan, without pulling in a serializer.
+enum Json {
+    String(String),
+    Number(usize),
+    Bool(bool),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Self::String(value) => write_json_string(out, value),
+            Self::Number(value) => {
+                let _ = write!(out, "{value}");
+            }
+            Self::Bool(value) => {
+                let _ = write!(out, "{value}");
+            }
+            Self::Array(values) if values.is_empty() => out.push_str("[]"),
+            Self::Array(values) => {
+                out.push_str("[\n");
+                for (index, value) in values.iter().enumerate() {
+                    push_indent(out, indent + 1);
+                    value.write(out, indent + 1);
+                    out.push_str(if index + 1 < values.len() {
+                        ",\n"
+                    } else {
+                        "\n"
+                    });
+                }
+                push_indent(out, indent);
+                out.push(']');
+            }
+            Self::Object(fields) => {
+                out.push_str("{\n");
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    push_indent(out, indent + 1);
+                    write_json_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                    out.push_str(if index + 1 < fields.len() {
+                        ",\n"
+                    } else {
+                        "\n"
+                    });
+                }
+                push_indent(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,295 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::app::{App, Plugin, PluginCollection, PluginDependency};
+use std::any::{TypeId, type_name};
+use std::fmt::{Debug, Formatter};
+use tracing::info;
+
+/// A named set of plugins that can be tweaked before it is added to the app.
+///
+/// ```
+/// use limnus_app::prelude::*;
+///
+/// struct LogPlugin;
+/// impl Plugin for LogPlugin {}
+///
+/// struct AudioPlugin;
+/// impl Plugin for AudioPlugin {}
+///
+/// struct MyPlugins;
+///
+/// impl PluginGroup for MyPlugins {
+///     fn builder(self) -> PluginGroupBuilder {
+///         PluginGroupBuilder::new().add(LogPlugin).add(AudioPlugin)
+///     }
+/// }
+///
+/// App::new().add_plugins(MyPlugins.builder().disable::<AudioPlugin>());
+/// ```
+pub trait PluginGroup {
+    fn builder(self) -> PluginGroupBuilder;
+}
+
+struct PluginGroupEntry {
+    plugin: Box<dyn Plugin>,
+    enabled: bool,
+}
+
+impl PluginGroupEntry {
+    fn new(plugin: Box<dyn Plugin>) -> Self {
+        Self {
+            plugin,
+            enabled: true,
+        }
+    }
+
+    /// The plugin type and the plugin types that it replaced.
+    fn provided_ids(&self) -> impl Iterator<Item = TypeId> {
+        std::iter::once(self.plugin.plugin_type_id()).chain(self.plugin.replaces())
+    }
+
+    fn depends_on_any(&self, type_ids: &[TypeId]) -> bool {
+        self.plugin.dependencies().iter().any(|dependency| {
+            matches!(dependency, PluginDependency::Plugin { type_id, .. } if type_ids.contains(type_id))
+        })
+    }
+}
+
+/// A plugin that stands in for the plugins it replaced, see [`PluginGroupBuilder::replace`].
+struct Replacement {
+    plugin: Box<dyn Plugin>,
+    replaces: Vec<TypeId>,
+}
+
+impl Plugin for Replacement {
+    fn build(&self, app: &mut App) {
+        self.plugin.build(app);
+    }
+
+    fn is_initialized(&self, app: &App) -> bool {
+        self.plugin.is_initialized(app)
+    }
+
+    fn post_initialization(&self, app: &mut App) {
+        self.plugin.post_initialization(app);
+    }
+
+    fn cleanup(&self, app: &mut App) {
+        self.plugin.cleanup(app);
+    }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        self.plugin.dependencies()
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.plugin.type_name()
+    }
+
+    fn plugin_type_id(&self) -> TypeId {
+        self.plugin.plugin_type_id()
+    }
+
+    fn replaces(&self) -> Vec<TypeId> {
+        self.replaces.clone()
+    }
+}
+
+/// Ordered list of plugins where members can be disabled, replaced or inserted relative to each other.
+///
+/// Methods that take a `Target` plugin type panic if the group has no plugin of that type.
+///
+/// A plugin that depends on a disabled plugin in the group, directly or through other plugins,
+/// is left out as well, see [`Self::disable`].
+#[derive(Default)]
+pub struct PluginGroupBuilder {
+    entries: Vec<PluginGroupEntry>,
+}
+
+impl Debug for PluginGroupBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(
+                self.entries
+                    .iter()
+                    .map(|entry| (entry.plugin.type_name(), entry.enabled)),
+            )
+            .finish()
+    }
+}
+
+impl PluginGroupBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds the plugin to the end of the group.
+    #[must_use]
+    #[allow(clippy::should_implement_trait)]
+    pub fn add<P: Plugin>(mut self, plugin: P) -> Self {
+        self.entries.push(PluginGroupEntry::new(Box::new(plugin)));
+        self
+    }
+
+    /// Adds all plugins in the collection to the end of the group.
+    #[must_use]
+    pub fn add_all<C: PluginCollection>(mut self, collection: C) -> Self {
+        self.entries.extend(
+            collection
+                .into_plugins()
+                .into_iter()
+                .map(PluginGroupEntry::new),
+        );
+        self
+    }
+
+    /// Inserts the plugin directly before the `Target` plugin.
+    #[must_use]
+    pub fn add_before<Target: Plugin, P: Plugin>(mut self, plugin: P) -> Self {
+        let index = self.index_of::<Target>();
+        self.entries
+            .insert(index, PluginGroupEntry::new(Box::new(plugin)));
+        self
+    }
+
+    /// Inserts the plugin directly after the `Target` plugin.
+    #[must_use]
+    pub fn add_after<Target: Plugin, P: Plugin>(mut self, plugin: P) -> Self {
+        let index = self.index_of::<Target>();
+        self.entries
+            .insert(index + 1, PluginGroupEntry::new(Box::new(plugin)));
+        self
+    }
+
+    /// Replaces the `Target` plugin with another plugin, keeping its position and enabled state.
+    ///
+    /// The replacement stands in for `Target`: plugins that depend on `Target` accept it instead,
+    /// and [`App::has_plugin`] is true for `Target`.
+    #[must_use]
+    pub fn replace<Target: Plugin, P: Plugin>(mut self, plugin: P) -> Self {
+        let index = self.index_of::<Target>();
+        let entry = &mut self.entries[index];
+        let mut replaces = plugin.replaces();
+        replaces.extend(entry.provided_ids());
+        replaces.retain(|&type_id| type_id != TypeId::of::<P>());
+        replaces.sort_unstable();
+        replaces.dedup();
+
+        entry.plugin = if replaces.is_empty() {
+            Box::new(plugin)
+        } else {
+            Box::new(Replacement {
+                plugin: Box::new(plugin),
+                replaces,
+            })
+        };
+        self
+    }
+
+    /// Replaces the plugin of the same type, e.g. to use different settings.
+    #[must_use]
+    pub fn set<P: Plugin>(self, plugin: P) -> Self {
+        self.replace::<P, P>(plugin)
+    }
+
+    /// The `Target` plugin keeps its position, but is not added to the app.
+    ///
+    /// The plugins in the group that depend on `Target`, directly or through other plugins,
+    /// are not added either, since they could not be built without it.
+    #[must_use]
+    pub fn disable<Target: Plugin>(mut self) -> Self {
+        let index = self.index_of::<Target>();
+        self.entries[index].enabled = false;
+        self
+    }
+
+    #[must_use]
+    pub fn enable<Target: Plugin>(mut self) -> Self {
+        let index = self.index_of::<Target>();
+        self.entries[index].enabled = true;
+        self
+    }
+
+    #[must_use]
+    pub fn contains<P: Plugin>(&self) -> bool {
+        self.position::<P>().is_some()
+    }
+
+    /// False for plugins that are disabled, and for plugins that depend on a disabled plugin.
+    #[must_use]
+    pub fn is_enabled<P: Plugin>(&self) -> bool {
+        self.position::<P>()
+            .is_some_and(|index| self.included()[index])
+    }
+
+    /// For each entry, if it is added to the app: it is enabled, and it does not depend on a plugin
+    /// that is left out.
+    fn included(&self) -> Vec<bool> {
+        let mut included: Vec<bool> = self.entries.iter().map(|entry| entry.enabled).collect();
+        loop {
+            let provided: Vec<TypeId> = self
+                .entries
+                .iter()
+                .zip(&included)
+                .filter(|(_, included)| **included)
+                .flat_map(|(entry, _)| entry.provided_ids())
+                .collect();
+            let left_out: Vec<TypeId> = self
+                .entries
+                .iter()
+                .zip(&included)
+                .filter(|(_, included)| !**included)
+                .flat_map(|(entry, _)| entry.provided_ids())
+                .filter(|type_id| !provided.contains(type_id))
+                .collect();
+
+            let mut changed = false;
+            for (entry, included) in self.entries.iter().zip(&mut included) {
+                if *included && entry.depends_on_any(&left_out) {
+                    *included = false;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return included;
+            }
+        }
+    }
+
+    fn position<P: Plugin>(&self) -> Option<usize> {
+        let type_id = TypeId::of::<P>();
+        self.entries
+            .iter()
+            .position(|entry| entry.plugin.plugin_type_id() == type_id)
+    }
+
+    fn index_of<P: Plugin>(&self) -> usize {
+        self.position::<P>().unwrap_or_else(|| {
+            panic!(
+                "plugin `{}` is not part of the plugin group",
+                type_name::<P>()
+            )
+        })
+    }
+}
+
+impl PluginCollection for PluginGroupBuilder {
+    fn into_plugins(self) -> Vec<Box<dyn Plugin>> {
+        let included = self.included();
+        self.entries
+            .into_iter()
+            .zip(included)
+            .filter_map(|(entry, included)| {
+                if !included && entry.enabled {
+                    info!(plugin = ?entry.plugin, "Left out, since it depends on a disabled plugin");
+                }
+                included.then_some(entry.plugin)
+            })
+            .collect()
+    }
+}
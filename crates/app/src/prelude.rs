@@ -2,4 +2,11 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
-pub use crate::app::{App, AppReturnValue, ApplicationExit, Plugin};
+pub use crate::app::{
+    AddSystemError, App, AppReturnValue, ApplicationExit, Plugin, PluginCollection,
+    PluginDependency, PluginError,
+};
+pub use crate::commands::Commands;
+pub use crate::headless::{HeadlessRunnerPlugin, HeadlessRunnerSettings, headless_runner};
+pub use crate::plan::{SchedulePlan, SchedulerPlan, StagePlan, SystemPlan};
+pub use crate::plugin_group::{PluginGroup, PluginGroupBuilder};
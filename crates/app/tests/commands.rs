@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_default_stages::{PostUpdate, Update};
+use limnus_local_resource::prelude::LocalResource;
+use limnus_message::prelude::Message;
+use limnus_resource::prelude::Resource;
+use limnus_scheduler::Scheduler;
+use limnus_stage::{ExecutorKind, Stages};
+use limnus_system::IntoSystem;
+use limnus_system_params::{LoRe, Msg, Re, ReAll, ReM};
+use limnus_system_state::State;
+use std::any::TypeId;
+
+#[derive(Debug)]
+struct UpdateScheduler;
+
+impl Scheduler for UpdateScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        for stage_id in [TypeId::of::<Update>(), TypeId::of::<PostUpdate>()] {
+            stages
+                .get_by_id(&stage_id)
+                .expect("stage missing")
+                .run(state);
+        }
+    }
+}
+
+#[derive(Debug, Resource)]
+struct Score(u32);
+
+#[derive(Debug, Default, Resource)]
+struct Seen {
+    score_in_same_stage: Option<bool>,
+    score: Option<u32>,
+    pings: usize,
+    window: bool,
+}
+
+#[derive(Debug, LocalResource)]
+struct Window;
+
+#[derive(Debug, Message)]
+struct Ping;
+
+fn record(mut commands: Commands) {
+    commands.insert_resource(Score(10));
+    commands.insert_local_resource(Window);
+    commands.send(Ping);
+}
+
+fn check_same_stage(mut resources: ReAll) {
+    let has_score = resources.get::<Score>().is_some();
+    resources.fetch_mut::<Seen>().score_in_same_stage = Some(has_score);
+}
+
+fn check_next_stage(score: Re<Score>, pings: Msg<Ping>, window: LoRe<Window>, mut seen: ReM<Seen>) {
+    let _ = window;
+    seen.score = Some(score.0);
+    seen.pings = pings.len_current();
+    seen.window = true;
+}
+
+fn create_app() -> App {
+    let mut app = App::new();
+    app.add_stage::<Update>();
+    app.add_stage::<PostUpdate>();
+    app.add_scheduler(UpdateScheduler);
+    app.insert_resource(Seen::default());
+    app
+}
+
+#[test]
+fn commands_are_applied_at_end_of_stage() {
+    let mut app = create_app();
+    app.add_system(Update, record);
+    app.add_system(Update, check_same_stage.after("record"));
+    app.add_system(Update, record.label("record"));
+    app.add_system(PostUpdate, check_next_stage);
+
+    app.update();
+
+    let seen = app.resource::<Seen>();
+    assert_eq!(seen.score_in_same_stage, Some(false));
+    assert_eq!(seen.score, Some(10));
+    assert_eq!(seen.pings, 2);
+    assert!(seen.window);
+}
+
+#[test]
+fn commands_in_parallel_stage() {
+    fn remove(mut commands: Commands) {
+        commands.remove_resource::<Score>();
+    }
+
+    let mut app = create_app();
+    app.set_stage_executor(Update, ExecutorKind::Parallel);
+    app.insert_resource(Score(1));
+    app.add_system(Update, record.after("remove"));
+    app.add_system(Update, remove.label("remove"));
+
+    app.update();
+
+    // commands are applied in the order the systems ran
+    assert_eq!(app.resource::<Score>().0, 10);
+}
+
+#[test]
+fn request_exit() {
+    fn quit(mut commands: Commands) {
+        commands.request_exit(AppReturnValue::Value(3));
+    }
+
+    let mut app = create_app();
+    app.add_system(Update, quit);
+    app.add_plugins(HeadlessRunnerPlugin::new(
+        HeadlessRunnerSettings::new().with_max_frames(10),
+    ));
+
+    assert_eq!(app.run(), AppReturnValue::Value(3));
+}
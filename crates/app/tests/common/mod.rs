@@ -0,0 +1,31 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_scheduler::Scheduler;
+use limnus_stage::Stages;
+use limnus_system_state::State;
+use std::any::TypeId;
+
+/// Runs the `Update` stage.
+#[derive(Debug)]
+pub struct UpdateScheduler;
+
+impl Scheduler for UpdateScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        stages
+            .get_by_id(&TypeId::of::<Update>())
+            .expect("stage missing")
+            .run(state);
+    }
+}
+
+/// An app with only the `Update` stage, which is run once for each `App::update`.
+pub fn update_app() -> App {
+    let mut app = App::new();
+    app.add_stage::<Update>();
+    app.add_scheduler(UpdateScheduler);
+    app
+}
@@ -0,0 +1,158 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_message::prelude::Message;
+use limnus_resource::prelude::Resource;
+use limnus_system::{IntoCondition, IntoSystem, System};
+use limnus_system_params::{MsgAll, Re, ReM, every_n_ticks, message_sent, resource_exists};
+
+#[derive(Debug, Default, Resource)]
+struct Counter {
+    value: u32,
+}
+
+#[derive(Debug, Resource)]
+struct Enabled(bool);
+
+#[derive(Debug, Resource)]
+struct Marker;
+
+#[derive(Debug, Message)]
+struct Ping;
+
+fn count(mut counter: ReM<Counter>) {
+    counter.value += 1;
+}
+
+fn is_enabled(enabled: Re<Enabled>) -> bool {
+    enabled.0
+}
+
+fn always() -> bool {
+    true
+}
+
+fn swap_messages(mut messages: MsgAll) {
+    messages.swap_all();
+}
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Counter::default());
+    app.insert_resource(Enabled(false));
+    app
+}
+
+fn counter(app: &App) -> u32 {
+    app.resource::<Counter>().value
+}
+
+#[test]
+fn run_if_resource_exists() {
+    let mut app = create_app();
+    app.add_system(Update, count.run_if(resource_exists::<Marker>));
+
+    app.update();
+    assert_eq!(counter(&app), 0);
+
+    app.insert_resource(Marker);
+    app.update();
+    assert_eq!(counter(&app), 1);
+}
+
+#[test]
+fn resource_exists_does_not_conflict_with_other_resources() {
+    fn toggle(mut enabled: ReM<Enabled>) {
+        enabled.0 = !enabled.0;
+    }
+
+    let guarded = count.run_if(resource_exists::<Marker>);
+
+    assert!(
+        guarded
+            .access()
+            .is_compatible(&toggle.into_system().access())
+    );
+}
+
+#[test]
+fn all_conditions_must_be_true() {
+    let mut app = create_app();
+    app.add_system(
+        Update,
+        count.run_if(always).run_if(is_enabled).run_if(always),
+    );
+
+    app.update();
+    assert_eq!(counter(&app), 0);
+
+    app.resource_mut::<Enabled>().0 = true;
+    app.update();
+    assert_eq!(counter(&app), 1);
+}
+
+#[test]
+fn combined_conditions() {
+    let mut app = create_app();
+    app.add_system(Update, count.run_if(is_enabled.not()));
+    app.add_system(Update, count.run_if(is_enabled.or(always)));
+    app.add_system(Update, count.run_if(is_enabled.and(always)));
+    app.add_system(
+        Update,
+        count.run_if(resource_exists::<Marker>.or(is_enabled).not()),
+    );
+
+    app.update();
+    assert_eq!(counter(&app), 3);
+
+    app.resource_mut::<Enabled>().0 = true;
+    app.update();
+    assert_eq!(counter(&app), 5);
+}
+
+#[test]
+fn condition_with_missing_param_is_false() {
+    let mut app = create_app();
+    app.resource_take::<Enabled>();
+    app.add_system(Update, count.run_if(is_enabled));
+    app.add_system(Update, count.run_if(is_enabled.not()));
+
+    app.update();
+    assert_eq!(counter(&app), 1);
+}
+
+#[test]
+fn run_every_n_ticks() {
+    let mut app = create_app();
+    app.add_system(Update, count.run_if(every_n_ticks(3)));
+
+    for _ in 0..7 {
+        app.update();
+    }
+
+    assert_eq!(counter(&app), 2);
+}
+
+#[test]
+fn run_if_message_sent() {
+    let mut app = create_app();
+    app.create_message_type::<Ping>();
+    app.add_system(Update, swap_messages);
+    app.add_system(Update, count.run_if(message_sent::<Ping>));
+
+    app.update();
+    assert_eq!(counter(&app), 0);
+
+    app.send(Ping);
+    app.update();
+    assert_eq!(counter(&app), 1);
+
+    app.update();
+    assert_eq!(counter(&app), 1);
+}
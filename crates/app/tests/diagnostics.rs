@@ -0,0 +1,168 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_resource::prelude::Resource;
+use limnus_scheduler::Scheduler;
+use limnus_stage::{ExecutorKind, FrameDiagnostics, Stage, StageId, StageTag, Stages};
+use limnus_system_params::{Re, ReM};
+use limnus_system_state::State;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[derive(Debug, Default, Resource)]
+struct Counter(u32);
+
+fn slow(mut counter: ReM<Counter>) {
+    counter.0 += 1;
+    sleep(Duration::from_millis(2));
+}
+
+fn fast(_counter: Re<Counter>) {}
+
+const STAGE: &str = "limnus_default_stages::Update";
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Counter::default());
+    app.add_system(Update, slow);
+    app.add_system(Update, fast);
+    app
+}
+
+#[test]
+fn records_systems_stages_and_schedulers() {
+    let mut app = create_app();
+    app.insert_resource(FrameDiagnostics::new(3));
+
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let diagnostics = app.resource::<FrameDiagnostics>();
+    let slow = diagnostics
+        .system(&StageId::of::<Update>(), "diagnostics::slow")
+        .unwrap();
+    assert_eq!(slow.len(), 3);
+    assert!(slow.average() >= Duration::from_millis(2));
+    assert!(slow.max() >= slow.average());
+    assert_eq!(diagnostics.systems()[0].1, "diagnostics::slow");
+    assert_eq!(diagnostics.systems()[1].1, "diagnostics::fast");
+
+    let stage = diagnostics.stage(&StageId::of::<Update>()).unwrap();
+    assert!(stage.last().unwrap() >= slow.last().unwrap());
+    let scheduler = diagnostics
+        .scheduler("diagnostics::common::UpdateScheduler")
+        .unwrap();
+    assert!(scheduler.last().unwrap() >= stage.last().unwrap());
+
+    let report = diagnostics.to_string();
+    assert!(report.contains("scheduler diagnostics::common::UpdateScheduler: avg"));
+    assert!(report.contains(&format!("system diagnostics::slow in {STAGE}: avg")));
+}
+
+#[test]
+fn parallel_stage_records_all_systems() {
+    let mut app = create_app();
+    app.set_stage_executor(Update, ExecutorKind::Parallel);
+    app.insert_resource(FrameDiagnostics::default());
+
+    app.update();
+
+    let diagnostics = app.resource::<FrameDiagnostics>();
+    assert_eq!(diagnostics.systems().len(), 2);
+    assert!(
+        diagnostics
+            .system(&StageId::of::<Update>(), "diagnostics::slow")
+            .unwrap()
+            .last()
+            .unwrap()
+            >= Duration::from_millis(2)
+    );
+}
+
+#[test]
+fn nothing_is_recorded_without_the_resource() {
+    let mut app = create_app();
+    app.update();
+    app.insert_resource(FrameDiagnostics::default());
+
+    assert!(app.resource::<FrameDiagnostics>().systems().is_empty());
+    assert_eq!(app.resource::<Counter>().0, 1);
+}
+
+#[test]
+fn stage_knows_system_names() {
+    struct Named;
+    impl limnus_stage::StageTag for Named {}
+
+    let mut stages = Stages::new();
+    let mut stage = Stage::new();
+    stage.add_system(slow);
+    stage.add_system(fast);
+    stages.add::<Named>(stage);
+
+    let stage = stages.get::<Named>().unwrap();
+    assert_eq!(
+        stage.system_names(),
+        ["diagnostics::slow", "diagnostics::fast"]
+    );
+    assert_eq!(
+        format!("{stage:?}"),
+        "diagnostics::stage_knows_system_names::Named"
+    );
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+struct Level(u32);
+
+impl StageTag for Level {
+    const CREATED_ON_DEMAND: bool = true;
+
+    fn stage_id(&self) -> StageId {
+        StageId::with_value::<Self, _>(*self)
+    }
+}
+
+#[derive(Debug)]
+struct LevelScheduler;
+
+impl Scheduler for LevelScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        for level in [Level(1), Level(2)] {
+            stages
+                .get_by_stage_id(&level.stage_id())
+                .expect("stage missing")
+                .run(state);
+        }
+    }
+}
+
+#[test]
+fn stages_with_a_tag_value_are_recorded_apart() {
+    let mut app = App::new();
+    app.add_scheduler(LevelScheduler);
+    app.insert_resource(Counter::default());
+    app.insert_resource(FrameDiagnostics::default());
+    app.add_system(Level(1), slow);
+    app.add_system(Level(2), fast);
+
+    app.update();
+
+    let diagnostics = app.resource::<FrameDiagnostics>();
+    assert_eq!(diagnostics.stages().len(), 2);
+    let first = diagnostics.stage(&Level(1).stage_id()).unwrap();
+    let second = diagnostics.stage(&Level(2).stage_id()).unwrap();
+    assert_eq!((first.len(), second.len()), (1, 1));
+    assert!(first.last().unwrap() >= Duration::from_millis(2));
+    assert!(
+        diagnostics
+            .system(&Level(2).stage_id(), "diagnostics::slow")
+            .is_none()
+    );
+}
@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_message::prelude::Message;
+use limnus_resource::prelude::Resource;
+use limnus_stage::ExecutorKind;
+use limnus_system::{IntoSystem, System};
+use limnus_system_params::{Msg, Re, ReM};
+use limnus_system_state::State;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
+
+#[derive(Debug, Default, Resource)]
+struct Log {
+    entries: Vec<&'static str>,
+}
+
+#[derive(Debug, Resource)]
+struct Spawned(u32);
+
+#[derive(Debug, Message)]
+struct Ping;
+
+fn spawn(state: &mut State) {
+    if state.resources().get::<Spawned>().is_none() {
+        state.resources_mut().insert(Spawned(7));
+        state.messages_mut().register_message_type::<Ping>();
+        state.messages_mut().send(Ping);
+    }
+    state.resource_mut::<Log>().unwrap().entries.push("spawn");
+}
+
+fn uses_spawned(spawned: Re<Spawned>, pings: Msg<Ping>, mut log: ReM<Log>) {
+    assert_eq!(spawned.0, 7);
+    assert_eq!(pings.len_current(), 1);
+    log.entries.push("uses_spawned");
+}
+
+fn before(mut log: ReM<Log>) {
+    log.entries.push("before");
+}
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Log::default());
+    app
+}
+
+#[test]
+fn exclusive_system_inserts_resources_and_messages() {
+    let mut app = create_app();
+    app.add_system(Update, uses_spawned.after("spawn"));
+    app.add_system(Update, spawn.label("spawn"));
+
+    app.update();
+    app.update();
+
+    assert_eq!(
+        app.resource::<Log>().entries,
+        ["spawn", "uses_spawned", "spawn", "uses_spawned"]
+    );
+}
+
+#[test]
+fn exclusive_system_runs_alone_in_parallel_stage() {
+    let mut app = create_app();
+    app.set_stage_executor(Update, ExecutorKind::DeterministicParallel);
+    app.add_system(Update, before);
+    app.add_system(Update, spawn);
+    app.add_system(Update, uses_spawned);
+
+    app.update();
+
+    assert_eq!(
+        app.resource::<Log>().entries,
+        ["before", "spawn", "uses_spawned"]
+    );
+}
+
+static EXCLUSIVE_RUNNING: AtomicBool = AtomicBool::new(false);
+static OVERLAPPED: AtomicBool = AtomicBool::new(false);
+
+fn slow_exclusive(_state: &mut State) {
+    EXCLUSIVE_RUNNING.store(true, Ordering::SeqCst);
+    sleep(Duration::from_millis(20));
+    EXCLUSIVE_RUNNING.store(false, Ordering::SeqCst);
+}
+
+fn no_params() {
+    if EXCLUSIVE_RUNNING.load(Ordering::SeqCst) {
+        OVERLAPPED.store(true, Ordering::SeqCst);
+    }
+    sleep(Duration::from_millis(20));
+}
+
+#[test]
+fn exclusive_system_does_not_run_next_to_system_without_params() {
+    let mut app = create_app();
+    app.set_stage_executor(Update, ExecutorKind::Parallel);
+    app.add_system(Update, no_params);
+    app.add_system(Update, slow_exclusive);
+    app.add_system(Update, no_params);
+
+    for _ in 0..5 {
+        app.update();
+    }
+
+    assert!(!OVERLAPPED.load(Ordering::SeqCst));
+}
+
+#[test]
+fn exclusive_access_conflicts_with_everything() {
+    let exclusive = spawn.into_system().access();
+
+    assert!(exclusive.is_exclusive());
+    assert!(!exclusive.is_compatible(&before.into_system().access()));
+    assert!(!exclusive.is_compatible(&no_params.into_system().access()));
+    assert!(exclusive.requires_main_thread());
+    assert_eq!(spawn.into_system().name(), "exclusive::spawn");
+}
@@ -0,0 +1,73 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_resource::prelude::Resource;
+use limnus_system_params::{ReAll, ReM};
+use std::time::Duration;
+
+#[derive(Debug, Resource)]
+struct FrameCounter {
+    count: u64,
+    exit_at: Option<u64>,
+}
+
+fn count_frames(mut counter: ReM<FrameCounter>) {
+    counter.count += 1;
+}
+
+fn exit_when_done(mut resources: ReAll) {
+    let counter = resources.fetch::<FrameCounter>();
+    if counter.exit_at == Some(counter.count) {
+        resources.insert(ApplicationExit {
+            value: AppReturnValue::Value(42),
+        });
+    }
+}
+
+fn create_app(exit_at: Option<u64>, settings: HeadlessRunnerSettings) -> App {
+    let mut app = update_app();
+    app.insert_resource(FrameCounter { count: 0, exit_at });
+    app.add_system(Update, count_frames);
+    app.add_system(Update, exit_when_done);
+    app.add_plugins(HeadlessRunnerPlugin::new(settings));
+    app
+}
+
+#[test]
+fn stops_after_max_frames() {
+    // would exit with 42 if the runner did a sixth update
+    let mut app = create_app(Some(6), HeadlessRunnerSettings::new().with_max_frames(5));
+
+    assert_eq!(app.run(), AppReturnValue::Value(0));
+}
+
+#[test]
+fn zero_max_frames_does_not_update() {
+    // would exit with 42 if the runner did an update
+    let mut app = create_app(Some(1), HeadlessRunnerSettings::new().with_max_frames(0));
+
+    assert_eq!(app.run(), AppReturnValue::Value(0));
+}
+
+#[test]
+fn returns_application_exit_value() {
+    let mut app = create_app(Some(3), HeadlessRunnerSettings::new().with_max_frames(100));
+
+    assert_eq!(app.run(), AppReturnValue::Value(42));
+}
+
+#[test]
+fn stops_after_max_duration() {
+    let settings = HeadlessRunnerSettings::new()
+        .with_frame_budget(Duration::from_millis(2))
+        .with_max_duration(Duration::from_millis(20));
+    let mut app = create_app(None, settings);
+
+    assert_eq!(app.run(), AppReturnValue::Value(0));
+}
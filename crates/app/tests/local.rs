@@ -0,0 +1,83 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_macros::SystemParam;
+use limnus_resource::prelude::Resource;
+use limnus_system::IntoSystem;
+use limnus_system_params::{Local, ReM};
+
+#[derive(Debug, Default, Resource)]
+struct Log {
+    entries: Vec<u32>,
+}
+
+fn count(mut counter: Local<u32>, mut log: ReM<Log>) {
+    *counter += 1;
+    log.entries.push(*counter);
+}
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Log::default());
+    app
+}
+
+#[test]
+fn each_system_instance_has_its_own_local() {
+    let mut app = create_app();
+    app.add_system(Update, count.label("first"));
+    app.add_system(Update, count.after("first"));
+
+    app.update();
+    app.update();
+
+    assert_eq!(app.resource::<Log>().entries, [1, 1, 2, 2]);
+}
+
+#[test]
+fn fn_mut_closure_keeps_its_captures() {
+    let mut app = create_app();
+    let mut total = 10;
+    app.add_system(Update, move |mut log: ReM<Log>| {
+        total += 5;
+        log.entries.push(total);
+    });
+
+    app.update();
+    app.update();
+
+    assert_eq!(app.resource::<Log>().entries, [15, 20]);
+}
+
+#[derive(SystemParam)]
+struct Counters<'a> {
+    frames: Local<'a, u32>,
+    seen: Local<'a, Vec<u32>>,
+    log: ReM<'a, Log>,
+}
+
+#[test]
+fn local_in_derived_param() {
+    fn track(mut counters: Counters) {
+        *counters.frames += 1;
+        let frames = *counters.frames;
+        counters.seen.push(frames);
+        let seen = counters.seen.len() as u32;
+        counters.log.entries.push(frames * 10 + seen);
+    }
+
+    let mut app = create_app();
+    app.add_system(Update, track);
+
+    app.update();
+    app.update();
+    app.update();
+
+    assert_eq!(app.resource::<Log>().entries, [11, 22, 33]);
+}
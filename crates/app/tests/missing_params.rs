@@ -0,0 +1,93 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_resource::prelude::Resource;
+use limnus_system::{MissingParamPolicy, MissingParams};
+use limnus_system_params::{Re, ReM};
+
+#[derive(Debug, Default, Resource)]
+struct Runs {
+    with_value: u32,
+    without_value: u32,
+}
+
+#[derive(Debug, Resource)]
+struct Missing(u32);
+
+fn optional(missing: Option<Re<Missing>>, mut runs: ReM<Runs>) {
+    match missing {
+        Some(missing) => runs.with_value += missing.0,
+        None => runs.without_value += 1,
+    }
+}
+
+fn required(_missing: Re<Missing>, mut runs: ReM<Runs>) {
+    runs.with_value += 1;
+}
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Runs::default());
+    app
+}
+
+#[test]
+fn optional_param_runs_without_the_resource() {
+    let mut app = create_app();
+    app.add_system(Update, optional);
+
+    app.update();
+    app.insert_resource(Missing(5));
+    app.update();
+
+    let runs = app.resource::<Runs>();
+    assert_eq!(runs.without_value, 1);
+    assert_eq!(runs.with_value, 5);
+}
+
+#[test]
+fn warn_once_skips_the_system() {
+    let mut app = create_app();
+    app.add_system(Update, required);
+
+    app.update();
+    app.update();
+
+    assert_eq!(app.resource::<Runs>().with_value, 0);
+}
+
+#[test]
+#[should_panic(expected = "required` could not run, param `limnus_system_params::Re<")]
+fn panic_policy() {
+    let mut app = create_app();
+    app.set_missing_param_policy(MissingParamPolicy::Panic);
+    app.add_system(Update, required);
+
+    app.update();
+}
+
+#[test]
+fn count_policy() {
+    let mut app = create_app();
+    app.set_missing_param_policy(MissingParamPolicy::Count);
+    app.add_system(Update, required);
+    app.add_system(Update, optional);
+
+    app.update();
+    app.update();
+
+    let missing_params = app.resource::<MissingParams>();
+    assert_eq!(missing_params.count("missing_params::required"), 2);
+    assert_eq!(missing_params.count("missing_params::optional"), 0);
+    let counts = missing_params.counts();
+    assert_eq!(counts.len(), 1);
+    assert_eq!(counts[0].0, "missing_params::required");
+    assert!(counts[0].1.ends_with("missing_params::Missing>"));
+    assert_eq!(counts[0].2, 2);
+}
@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_resource::prelude::Resource;
+use limnus_system::{IntoSystem, OneShotSystems, RunSystemError, System, SystemId};
+use limnus_system_params::{Local, Re, ReM};
+use limnus_system_state::State;
+
+#[derive(Debug, Default, Resource)]
+struct Log {
+    entries: Vec<String>,
+}
+
+#[derive(Debug, Resource)]
+struct Clicked(SystemId);
+
+fn on_click(mut clicks: Local<u32>, mut log: ReM<Log>) {
+    *clicks += 1;
+    log.entries.push(format!("click {}", *clicks));
+}
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Log::default());
+    app
+}
+
+#[test]
+fn run_from_app() {
+    let mut app = create_app();
+    let first = app.register_system(on_click);
+    let second = app.register_system(on_click);
+
+    app.run_system(first).unwrap();
+    app.run_system(first).unwrap();
+    app.run_system(second).unwrap();
+
+    assert_eq!(
+        app.resource::<Log>().entries,
+        ["click 1", "click 2", "click 1"]
+    );
+}
+
+#[test]
+fn run_from_system_is_deferred() {
+    fn button(clicked: Re<Clicked>, mut commands: Commands, mut log: ReM<Log>) {
+        commands.run_system(clicked.0);
+        log.entries.push("button".to_string());
+    }
+    fn after_button(mut log: ReM<Log>) {
+        log.entries.push("after button".to_string());
+    }
+
+    let mut app = create_app();
+    let id = app.register_system(on_click);
+    app.insert_resource(Clicked(id));
+    app.add_system(Update, button.label("button"));
+    app.add_system(Update, after_button.after("button"));
+
+    app.update();
+
+    assert_eq!(
+        app.resource::<Log>().entries,
+        ["button", "after button", "click 1"]
+    );
+}
+
+#[test]
+fn commands_of_one_shot_system_are_applied() {
+    fn reset(mut commands: Commands) {
+        commands.remove_resource::<Log>();
+    }
+
+    let mut app = create_app();
+    let id = app.register_system(reset);
+
+    app.run_system(id).unwrap();
+
+    assert!(!app.has_resource::<Log>());
+}
+
+#[test]
+fn missing_and_failing_systems() {
+    fn fail() -> Result<(), String> {
+        Err("nope".to_string())
+    }
+
+    let mut app = create_app();
+    let failing = app.register_system(fail);
+    let removed = app.register_system(on_click);
+    assert!(app.resource_mut::<OneShotSystems>().remove(removed));
+
+    assert!(matches!(
+        app.run_system(removed),
+        Err(RunSystemError::NotRegistered(id)) if id == removed
+    ));
+    assert_eq!(
+        app.run_system(failing).unwrap_err().to_string(),
+        "one-shot system `one_shot::missing_and_failing_systems::fail` failed: nope"
+    );
+}
+
+#[test]
+fn exclusive_one_shot_system_can_register_more() {
+    fn register(state: &mut State) {
+        let id = state
+            .resource_mut::<OneShotSystems>()
+            .unwrap()
+            .register(on_click);
+        state.resources_mut().insert(Clicked(id));
+    }
+
+    let mut app = create_app();
+    let id = app.register_system(register);
+    app.run_system(id).unwrap();
+    let registered = app.resource::<Clicked>().0;
+    app.run_system(registered).unwrap();
+
+    assert_eq!(app.resource::<Log>().entries, ["click 1"]);
+    assert_eq!(
+        on_click.into_system().name(),
+        app.resource::<OneShotSystems>().name(registered).unwrap()
+    );
+}
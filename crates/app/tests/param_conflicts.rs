@@ -0,0 +1,156 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_local_resource::prelude::LocalResource;
+use limnus_message::prelude::Message;
+use limnus_resource::prelude::Resource;
+use limnus_system::{Access, AccessConflict, IntoSystem, Storage, SystemParam};
+use limnus_system_params::{LoRe, LoReAll, LoReM, Msg, MsgAll, MsgM, Re, ReAll, ReM};
+
+#[derive(Debug, Resource)]
+struct Foo;
+
+#[derive(Debug, Resource)]
+struct Bar;
+
+#[derive(Debug, LocalResource)]
+struct Window;
+
+#[derive(Debug, Message)]
+struct Ping;
+
+fn conflicts<Params: SystemParam>() -> Vec<AccessConflict> {
+    let mut access = Access::default();
+    Params::access(&mut access);
+    access.conflicts()
+}
+
+#[test]
+fn shared_borrows_do_not_conflict() {
+    fn reads(_a: Re<Foo>, _b: Re<Foo>, _c: ReM<Bar>, _d: Msg<Ping>, _e: Msg<Ping>) {}
+
+    let mut app = App::new();
+    app.add_stage::<Update>();
+    app.add_system(Update, reads);
+
+    assert!(conflicts::<(Re<Foo>, Re<Foo>)>().is_empty());
+    assert!(conflicts::<(ReAll, LoReAll, MsgAll)>().is_empty());
+}
+
+#[test]
+fn mutable_and_shared_borrow_conflict() {
+    assert_eq!(
+        conflicts::<(ReM<Foo>, Re<Foo>)>(),
+        [AccessConflict {
+            storage: Storage::Resources,
+            type_name: Some(std::any::type_name::<Foo>()),
+        }]
+    );
+    assert_eq!(conflicts::<(MsgM<Ping>, MsgM<Ping>)>().len(), 1);
+}
+
+#[test]
+fn whole_storage_conflicts_with_any_borrow() {
+    assert_eq!(
+        conflicts::<(ReAll, Re<Foo>)>(),
+        [AccessConflict {
+            storage: Storage::Resources,
+            type_name: None,
+        }]
+    );
+    assert_eq!(conflicts::<(LoRe<Window>, LoReAll)>().len(), 1);
+    assert_eq!(conflicts::<(MsgAll, Msg<Ping>)>().len(), 1);
+}
+
+#[derive(Debug, Default, Resource)]
+struct Counter(u32);
+
+#[derive(Debug, Default, LocalResource)]
+struct LocalCounter(u32);
+
+fn count_all(
+    mut counter: ReM<Counter>,
+    unchanged: Re<Foo>,
+    mut local: LoReM<LocalCounter>,
+    pings: Msg<Ping>,
+    mut commands: Commands,
+) {
+    let _ = (&*unchanged, pings.len_current());
+    counter.0 += 1;
+    local.0 += counter.0;
+    commands.insert_resource(Bar);
+}
+
+#[test]
+fn params_borrow_their_own_types_of_the_state() {
+    let mut app = App::new();
+    app.insert_resource(Counter::default());
+    app.insert_resource(Foo);
+    app.insert_local_resource(LocalCounter::default());
+    app.create_message_type::<Ping>();
+    let id = app.register_system(count_all);
+
+    app.run_system(id).unwrap();
+    app.run_system(id).unwrap();
+
+    assert_eq!(app.resource::<Counter>().0, 2);
+    assert_eq!(app.local_resources().fetch::<LocalCounter>().0, 3);
+    assert!(app.has_resource::<Bar>());
+}
+
+#[test]
+#[should_panic(expected = "aliasing` has conflicting params: resource `param_conflicts::Foo`")]
+fn add_system_rejects_conflicting_params() {
+    fn aliasing(_a: ReM<Foo>, _b: Re<Foo>) {}
+
+    let mut app = App::new();
+    app.add_stage::<Update>();
+    app.add_system(Update, aliasing);
+}
+
+#[test]
+#[should_panic(expected = "everything` has conflicting params: the resource storage")]
+fn add_system_rejects_whole_storage_with_other_borrow() {
+    fn everything(_a: ReAll, _b: ReM<Foo>) {}
+
+    let mut app = App::new();
+    app.add_stage::<Update>();
+    app.add_system(Update, everything.label("everything"));
+}
+
+#[test]
+#[should_panic(expected = "check` has conflicting params: the message storage")]
+fn run_if_rejects_conflicting_params() {
+    fn check(_a: MsgAll, _b: MsgM<Ping>) -> bool {
+        true
+    }
+    fn system() {}
+
+    let mut app = App::new();
+    app.add_stage::<Update>();
+    app.add_system(Update, system.run_if(check));
+}
+
+#[test]
+fn commands_conflict_with_exclusive_access() {
+    let mut commands = Access::default();
+    Commands::access(&mut commands);
+
+    assert!(commands.reads_commands());
+    assert!(commands.is_compatible(&commands));
+    assert!(!commands.is_compatible(&Access::exclusive()));
+
+    commands.add_exclusive();
+    let conflicts = commands.conflicts();
+    assert!(conflicts.contains(&AccessConflict {
+        storage: Storage::CommandQueue,
+        type_name: None,
+    }));
+    assert_eq!(
+        conflicts.last().unwrap().to_string(),
+        "the command queue is borrowed by one param, and the whole state by another param"
+    );
+}
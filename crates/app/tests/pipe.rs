@@ -0,0 +1,157 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_message::prelude::Message;
+use limnus_resource::prelude::Resource;
+use limnus_system::{In, IntoSystem, PipeFunction, System, SystemErrorHandler};
+use limnus_system_params::{Local, MsgM, Re, ReM};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Resource)]
+struct Score(i32);
+
+#[derive(Debug, Default, Resource)]
+struct Log {
+    entries: Vec<String>,
+}
+
+#[derive(Debug, Message)]
+struct Failed(String);
+
+fn parse_score(score: Re<Score>) -> Result<u32, String> {
+    u32::try_from(score.0).map_err(|_| format!("negative score {}", score.0))
+}
+
+fn send_errors<T: Send + 'static>(In(result): In<Result<T, String>>, mut failed: MsgM<Failed>) {
+    if let Err(err) = result {
+        failed.send(Failed(err));
+    }
+}
+
+fn log_failures(In(()): In<()>, failed: MsgM<Failed>, mut log: ReM<Log>) {
+    for Failed(err) in failed.iter_current() {
+        log.entries.push(err.clone());
+    }
+}
+
+fn create_app(score: i32) -> App {
+    let mut app = update_app();
+    app.create_message_type::<Failed>();
+    app.insert_resource(Score(score));
+    app.insert_resource(Log::default());
+    app
+}
+
+#[test]
+fn output_is_passed_to_next_function() {
+    fn double(In(value): In<Result<u32, String>>, mut score: ReM<Score>) {
+        score.0 = value.unwrap() as i32 * 2;
+    }
+
+    let mut app = create_app(21);
+    app.add_system(Update, parse_score.pipe(double));
+
+    app.update();
+
+    assert_eq!(app.resource::<Score>().0, 42);
+}
+
+#[test]
+fn chained_adapters() {
+    let mut app = create_app(-3);
+    app.add_system(Update, parse_score.pipe(send_errors).pipe(log_failures));
+
+    app.update();
+
+    assert_eq!(app.resource::<Log>().entries, ["negative score -3"]);
+}
+
+#[test]
+fn piped_functions_keep_their_own_locals() {
+    fn count(mut runs: Local<u32>) -> u32 {
+        *runs += 1;
+        *runs
+    }
+    fn sum(In(runs): In<u32>, mut total: Local<u32>, mut log: ReM<Log>) {
+        *total += runs;
+        log.entries.push(format!("{runs} {}", *total));
+    }
+
+    let mut app = create_app(0);
+    app.add_system(Update, count.pipe(sum));
+
+    app.update();
+    app.update();
+    app.update();
+
+    assert_eq!(app.resource::<Log>().entries, ["1 1", "2 3", "3 6"]);
+}
+
+#[test]
+fn piped_result_goes_to_error_handler() {
+    fn check(In(result): In<Result<u32, String>>) -> Result<(), String> {
+        result.map(|_| ())
+    }
+
+    let mut app = create_app(-1);
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let handler_errors = Arc::clone(&errors);
+    app.set_system_error_handler(SystemErrorHandler::custom(move |error| {
+        handler_errors.lock().unwrap().push(error.to_string());
+    }));
+    app.add_system(Update, parse_score.pipe(check).label("check"));
+
+    app.update();
+
+    assert_eq!(
+        *errors.lock().unwrap(),
+        [
+            "system `pipe::parse_score` in stage `limnus_default_stages::Update` failed: negative score -1"
+        ]
+    );
+}
+
+#[test]
+fn functions_in_a_pipe_may_borrow_the_same_data() {
+    fn reset(mut score: ReM<Score>) -> i32 {
+        std::mem::replace(&mut score.0, 0)
+    }
+    fn record(In(previous): In<i32>, score: Re<Score>, mut log: ReM<Log>) {
+        log.entries.push(format!("{previous} -> {}", score.0));
+    }
+
+    let system = reset.pipe(record).into_system();
+    let access = system.access();
+    assert_eq!(access.resources().write_names().len(), 2);
+
+    let mut app = create_app(7);
+    app.add_system(Update, reset.pipe(record));
+
+    app.update();
+
+    assert_eq!(app.resource::<Log>().entries, ["7 -> 0"]);
+}
+
+#[test]
+#[should_panic(expected = "has conflicting params: resource `pipe::Score`")]
+fn conflicts_within_one_function_are_rejected() {
+    fn aliasing(In(()): In<()>, _a: ReM<Score>, _b: Re<Score>) {}
+    fn start() {}
+
+    let _ = start.pipe(aliasing);
+}
+
+#[test]
+#[should_panic(expected = "has conflicting params: resource `pipe::Score`")]
+fn conflicts_within_the_first_function_are_rejected() {
+    fn aliasing(_a: ReM<Score>, _b: Re<Score>) {}
+    fn sink(In(()): In<()>) {}
+
+    let _ = aliasing.pipe(sink);
+}
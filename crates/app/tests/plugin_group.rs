@@ -0,0 +1,195 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_resource::prelude::Resource;
+
+#[derive(Debug, Default, Resource)]
+pub struct BuildOrder {
+    pub names: Vec<&'static str>,
+}
+
+fn record(app: &mut App, name: &'static str) {
+    if !app.has_resource::<BuildOrder>() {
+        app.insert_resource(BuildOrder::default());
+    }
+    app.resource_mut::<BuildOrder>().names.push(name);
+}
+
+macro_rules! recording_plugin {
+    ($name:ident) => {
+        pub struct $name;
+
+        impl Plugin for $name {
+            fn build(&self, app: &mut App) {
+                record(app, stringify!($name));
+            }
+        }
+    };
+}
+
+recording_plugin!(First);
+recording_plugin!(Second);
+recording_plugin!(Third);
+recording_plugin!(Extra);
+recording_plugin!(P1);
+recording_plugin!(P2);
+recording_plugin!(P3);
+recording_plugin!(P4);
+recording_plugin!(P5);
+recording_plugin!(P6);
+recording_plugin!(P7);
+
+/// Records its name, and depends on the `Dependency` plugin.
+macro_rules! dependent_plugin {
+    ($name:ident, $dependency:ident) => {
+        pub struct $name;
+
+        impl Plugin for $name {
+            fn build(&self, app: &mut App) {
+                record(app, stringify!($name));
+            }
+
+            fn dependencies(&self) -> Vec<PluginDependency> {
+                vec![PluginDependency::plugin::<$dependency>()]
+            }
+        }
+    };
+}
+
+dependent_plugin!(NeedsSecond, Second);
+dependent_plugin!(NeedsNeedsSecond, NeedsSecond);
+
+pub struct Configurable {
+    pub name: &'static str,
+}
+
+impl Plugin for Configurable {
+    fn build(&self, app: &mut App) {
+        record(app, self.name);
+    }
+}
+
+pub struct MyGroup;
+
+impl PluginGroup for MyGroup {
+    fn builder(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::new()
+            .add(First)
+            .add(Second)
+            .add(Configurable { name: "default" })
+            .add(Third)
+    }
+}
+
+fn build_order(collection: impl PluginCollection) -> Vec<&'static str> {
+    let mut app = App::new();
+    app.add_plugins(collection);
+    app.resource::<BuildOrder>().names.clone()
+}
+
+#[test]
+fn group_in_order() {
+    assert_eq!(
+        build_order(MyGroup.builder()),
+        ["First", "Second", "default", "Third"]
+    );
+}
+
+#[test]
+fn disable_plugin() {
+    let builder = MyGroup.builder().disable::<Second>();
+    assert!(builder.contains::<Second>());
+    assert!(!builder.is_enabled::<Second>());
+
+    assert_eq!(build_order(builder), ["First", "default", "Third"]);
+}
+
+#[test]
+fn replace_plugin() {
+    assert_eq!(
+        build_order(MyGroup.builder().replace::<Second, _>(Extra)),
+        ["First", "Extra", "default", "Third"]
+    );
+}
+
+#[test]
+fn set_plugin_settings() {
+    assert_eq!(
+        build_order(MyGroup.builder().set(Configurable { name: "custom" })),
+        ["First", "Second", "custom", "Third"]
+    );
+}
+
+#[test]
+fn add_before_and_after() {
+    assert_eq!(
+        build_order(
+            MyGroup
+                .builder()
+                .add_before::<First, _>(Extra)
+                .add_after::<Third, _>(P1)
+        ),
+        ["Extra", "First", "Second", "default", "Third", "P1"]
+    );
+}
+
+#[test]
+#[should_panic(expected = "is not part of the plugin group")]
+fn disable_unknown_plugin() {
+    let _ = MyGroup.builder().disable::<Extra>();
+}
+
+#[test]
+fn large_and_nested_collections() {
+    assert_eq!(
+        build_order((
+            First,
+            Second,
+            Third,
+            Extra,
+            P1,
+            P2,
+            (P3, P4, P5),
+            P6,
+            P7,
+            Configurable { name: "last" },
+        )),
+        [
+            "First", "Second", "Third", "Extra", "P1", "P2", "P3", "P4", "P5", "P6", "P7", "last"
+        ]
+    );
+}
+
+#[test]
+fn disable_leaves_out_the_plugins_that_depend_on_it() {
+    let builder = MyGroup
+        .builder()
+        .add(NeedsSecond)
+        .add(NeedsNeedsSecond)
+        .disable::<Second>();
+    assert!(!builder.is_enabled::<NeedsSecond>());
+    assert!(!builder.is_enabled::<NeedsNeedsSecond>());
+
+    assert_eq!(build_order(builder), ["First", "default", "Third"]);
+}
+
+#[test]
+fn replacement_satisfies_dependencies_on_the_replaced_plugin() {
+    let mut app = App::new();
+    app.add_plugins(
+        MyGroup
+            .builder()
+            .add(NeedsSecond)
+            .replace::<Second, _>(Extra)
+            .replace::<Extra, _>(P1),
+    );
+
+    assert_eq!(
+        app.resource::<BuildOrder>().names,
+        ["First", "P1", "default", "Third", "NeedsSecond"]
+    );
+    assert!(app.has_plugin::<Second>());
+    assert!(app.has_plugin::<Extra>());
+}
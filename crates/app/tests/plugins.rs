@@ -0,0 +1,150 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_resource::prelude::Resource;
+
+#[derive(Debug, Resource)]
+pub struct Sender;
+
+pub struct SenderPlugin;
+
+impl Plugin for SenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Sender);
+    }
+}
+
+pub struct ReceiverPlugin;
+
+impl Plugin for ReceiverPlugin {
+    fn build(&self, app: &mut App) {
+        assert!(app.has_resource::<Sender>());
+    }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![PluginDependency::plugin::<SenderPlugin>()]
+    }
+}
+
+pub struct NeedsSenderResourcePlugin;
+
+impl Plugin for NeedsSenderResourcePlugin {
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![PluginDependency::resource::<Sender>()]
+    }
+}
+
+#[test]
+fn dependency_added_before() {
+    let mut app = App::new();
+
+    app.add_plugins((SenderPlugin, ReceiverPlugin, NeedsSenderResourcePlugin));
+
+    assert!(app.has_plugin::<ReceiverPlugin>());
+}
+
+#[test]
+fn dependency_added_in_earlier_call() {
+    let mut app = App::new();
+    app.add_plugins(SenderPlugin);
+
+    assert!(app.try_add_plugins(ReceiverPlugin).is_ok());
+}
+
+#[test]
+fn missing_plugin() {
+    let mut app = App::new();
+
+    let err = app.try_add_plugins(ReceiverPlugin).err().unwrap();
+
+    assert_eq!(
+        err,
+        PluginError::MissingPlugin {
+            plugin: "plugins::ReceiverPlugin",
+            dependency: "plugins::SenderPlugin",
+        }
+    );
+    assert!(!app.has_plugin::<ReceiverPlugin>());
+}
+
+#[test]
+fn misordered_plugin() {
+    let mut app = App::new();
+
+    let err = app
+        .try_add_plugins((ReceiverPlugin, SenderPlugin))
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        PluginError::MisorderedPlugin {
+            plugin: "plugins::ReceiverPlugin",
+            dependency: "plugins::SenderPlugin",
+        }
+    );
+}
+
+#[test]
+fn no_plugin_is_built_if_a_later_plugin_fails() {
+    let mut app = App::new();
+
+    let err = app
+        .try_add_plugins((SenderPlugin, ReceiverPlugin, SenderPlugin))
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        PluginError::Duplicate {
+            plugin: "plugins::SenderPlugin",
+        }
+    );
+    assert!(!app.has_plugin::<SenderPlugin>());
+    assert!(!app.has_resource::<Sender>());
+}
+
+#[test]
+fn duplicate_plugin() {
+    let mut app = App::new();
+
+    let err = app
+        .try_add_plugins((SenderPlugin, SenderPlugin))
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        PluginError::Duplicate {
+            plugin: "plugins::SenderPlugin",
+        }
+    );
+}
+
+#[test]
+fn missing_resource() {
+    let mut app = App::new();
+
+    let err = app
+        .try_add_plugins(NeedsSenderResourcePlugin)
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        PluginError::MissingResource {
+            plugin: "plugins::NeedsSenderResourcePlugin",
+            resource: "plugins::Sender",
+        }
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "plugin `plugins::ReceiverPlugin` depends on plugin `plugins::SenderPlugin`, which must be added before it"
+)]
+fn add_plugins_panics_with_readable_message() {
+    App::new().add_plugins((ReceiverPlugin, SenderPlugin));
+}
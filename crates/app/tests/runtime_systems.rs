@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_resource::prelude::Resource;
+use limnus_stage::{ExecutorKind, StageTag, SystemHandle};
+use limnus_system::IntoSystem;
+use limnus_system_params::{Re, ReM};
+
+#[derive(Debug, Default, Resource)]
+struct Log(Vec<&'static str>);
+
+fn first(mut log: ReM<Log>) {
+    log.0.push("first");
+}
+
+fn second(mut log: ReM<Log>) {
+    log.0.push("second");
+}
+
+fn third(mut log: ReM<Log>) {
+    log.0.push("third");
+}
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Log::default());
+    app
+}
+
+fn take_log(app: &mut App) -> Vec<&'static str> {
+    std::mem::take(&mut app.resource_mut::<Log>().0)
+}
+
+#[test]
+fn remove_and_disable_between_updates() {
+    let mut app = create_app();
+    let first_handle = app.add_system(Update, first);
+    let second_handle = app.add_system(Update, second);
+    app.add_system(Update, third);
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["first", "second", "third"]);
+
+    assert!(app.set_system_enabled(&second_handle, false));
+    assert_eq!(app.is_system_enabled(&second_handle), Some(false));
+    app.update();
+    assert_eq!(take_log(&mut app), ["first", "third"]);
+
+    assert!(app.remove_system(&first_handle));
+    assert!(!app.remove_system(&first_handle));
+    assert_eq!(app.is_system_enabled(&first_handle), None);
+    assert!(app.set_system_enabled(&second_handle, true));
+    app.update();
+    assert_eq!(take_log(&mut app), ["second", "third"]);
+}
+
+#[test]
+fn removing_keeps_the_sorted_order() {
+    let mut app = create_app();
+    app.add_system(Update, third.after("second"));
+    let second_handle = app.add_system(Update, second.label("second").after("first"));
+    app.add_system(Update, first.label("first"));
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["first", "second", "third"]);
+
+    app.remove_system(&second_handle);
+    app.update();
+    assert_eq!(take_log(&mut app), ["first", "third"]);
+}
+
+#[test]
+fn parallel_stage_skips_disabled_systems() {
+    let mut app = create_app();
+    app.set_stage_executor(Update, ExecutorKind::DeterministicParallel);
+    app.add_system(Update, first);
+    let second_handle = app.add_system(Update, second);
+    app.add_system(Update, third);
+    app.set_system_enabled(&second_handle, false);
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["first", "third"]);
+
+    app.remove_system(&second_handle);
+    app.add_system(Update, second);
+    app.update();
+    assert_eq!(take_log(&mut app), ["first", "third", "second"]);
+}
+
+#[test]
+fn constraints_on_removed_labels_are_met() {
+    let mut app = create_app();
+    app.add_system(Update, third.after("second"));
+    let second_handle = app.add_system(Update, second.label("second"));
+    app.update();
+    take_log(&mut app);
+
+    app.remove_system(&second_handle);
+    app.add_system(Update, first.before("second"));
+    app.update();
+    assert_eq!(take_log(&mut app), ["third", "first"]);
+
+    app.add_system(Update, second.label("second"));
+    app.update();
+    assert_eq!(take_log(&mut app), ["first", "second", "third"]);
+}
+
+#[test]
+fn try_add_system_to_missing_stage() {
+    struct Missing;
+    impl StageTag for Missing {}
+
+    let mut app = create_app();
+    let err = app.try_add_system(Missing, first).unwrap_err();
+
+    assert!(matches!(err, AddSystemError::MissingStage { .. }));
+    assert!(err.to_string().contains("Missing"));
+}
+
+#[derive(Debug, Resource)]
+struct Handles {
+    added: Option<SystemHandle>,
+    own: Option<SystemHandle>,
+}
+
+fn swap_in_third(mut commands: Commands, mut handles: ReM<Handles>) {
+    if handles.added.is_none() {
+        handles.added = Some(commands.add_system(Update, third));
+    } else if let Some(own) = handles.own.take() {
+        commands.remove_system(&own);
+    }
+}
+
+#[test]
+fn commands_change_systems_after_the_update() {
+    let mut app = create_app();
+    app.add_system(Update, first);
+    let own = app.add_system(Update, swap_in_third);
+    app.insert_resource(Handles {
+        added: None,
+        own: Some(own.clone()),
+    });
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["first"]);
+    let added = app.resource::<Handles>().added.clone().unwrap();
+    assert_eq!(app.is_system_enabled(&added), Some(true));
+
+    app.update();
+    assert_eq!(take_log(&mut app), ["first", "third"]);
+    assert_eq!(app.is_system_enabled(&own), None);
+
+    app.set_system_enabled(&added, false);
+    app.update();
+    assert_eq!(take_log(&mut app), ["first"]);
+}
+
+fn disable_reader(mut commands: Commands, handles: Re<Handles>) {
+    commands.set_system_enabled(handles.own.as_ref().unwrap(), false);
+}
+
+#[test]
+fn disabled_systems_are_marked_in_the_plan() {
+    let mut app = create_app();
+    let handle = app.add_system(Update, first);
+    app.insert_resource(Handles {
+        added: None,
+        own: Some(handle.clone()),
+    });
+    let id = app.register_system(disable_reader);
+    app.run_system(id).unwrap();
+
+    assert_eq!(app.is_system_enabled(&handle), Some(false));
+    let plan = app.schedule_plan().unwrap();
+    assert!(!plan.unscheduled[0].systems[0].enabled);
+    assert!(plan.to_json().contains("\"enabled\": false"));
+    assert!(plan.to_dot().contains("[shape=box, style=dashed, label="));
+}
@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_default_stages::{First, PostUpdate, Update};
+use limnus_message::prelude::Message;
+use limnus_resource::prelude::Resource;
+use limnus_scheduler::Scheduler;
+use limnus_stage::{ExecutorKind, StageId, Stages};
+use limnus_system::IntoSystem;
+use limnus_system_params::{Msg, Re, ReM};
+use limnus_system_state::State;
+
+#[derive(Debug)]
+struct MainScheduler;
+
+impl Scheduler for MainScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        for stage_id in self.stage_ids() {
+            stages.get_by_stage_id(&stage_id).unwrap().run(state);
+        }
+    }
+
+    fn stage_ids(&self) -> Vec<StageId> {
+        vec![StageId::of::<Update>(), StageId::of::<PostUpdate>()]
+    }
+}
+
+/// Does not list its stages
+#[derive(Debug)]
+struct OpaqueScheduler;
+
+impl Scheduler for OpaqueScheduler {
+    fn schedule(&self, _stages: &Stages, _state: &mut State) {}
+}
+
+#[derive(Debug, Resource)]
+struct Score(u32);
+
+#[derive(Debug, Message)]
+struct Hit;
+
+fn read_input() {}
+
+fn apply_hits(hits: Msg<Hit>, mut score: ReM<Score>) {
+    score.0 += hits.len_current() as u32;
+}
+
+fn show_score(_score: Re<Score>) {}
+
+fn create_app() -> App {
+    let mut app = App::new();
+    app.add_stage::<First>();
+    app.add_stage::<Update>();
+    app.add_stage::<PostUpdate>();
+    app.add_scheduler(MainScheduler);
+    app.add_scheduler(OpaqueScheduler);
+    app.set_stage_executor(Update, ExecutorKind::Parallel);
+    app.add_system(Update, apply_hits.after("input"));
+    app.add_system(Update, read_input.label("input"));
+    app.add_system(PostUpdate, show_score);
+    app.add_system(First, read_input);
+    app
+}
+
+#[test]
+fn plan_follows_runner_and_stage_order() {
+    let plan = create_app().schedule_plan().unwrap();
+
+    assert!(plan.startup.is_empty());
+    let names: Vec<_> = plan.update.iter().map(|scheduler| scheduler.name).collect();
+    assert_eq!(
+        names,
+        [
+            "schedule_export::MainScheduler",
+            "schedule_export::OpaqueScheduler"
+        ]
+    );
+    assert!(plan.update[1].stages.is_empty());
+
+    let update = &plan.update[0].stages[0];
+    assert_eq!(update.name, "limnus_default_stages::Update");
+    assert_eq!(update.executor, ExecutorKind::Parallel);
+    assert_eq!(update.systems[0].name, "schedule_export::read_input");
+    assert_eq!(update.systems[0].labels, ["input"]);
+    let apply_hits = &update.systems[1];
+    assert_eq!(apply_hits.after, ["input"]);
+    assert_eq!(apply_hits.runs_after, [0]);
+    assert_eq!(apply_hits.reads, ["message schedule_export::Hit"]);
+    assert_eq!(apply_hits.writes, ["resource schedule_export::Score"]);
+
+    assert_eq!(plan.unscheduled.len(), 1);
+    assert_eq!(plan.unscheduled[0].name, "limnus_default_stages::First");
+}
+
+#[test]
+fn json_export() {
+    let json = create_app().schedule_plan().unwrap().to_json();
+
+    assert!(json.starts_with("{\n  \"startup\": [],\n  \"update\": [\n"));
+    assert!(json.contains("\"name\": \"schedule_export::MainScheduler\""));
+    assert!(json.contains("\"executor\": \"Parallel\""));
+    assert!(json.contains(
+        "\"reads\": [\n                \"message schedule_export::Hit\"\n              ]"
+    ));
+    assert!(json.contains("\"runs_after\": [\n                0\n              ]"));
+    assert!(json.ends_with("}\n"));
+}
+
+#[test]
+fn dot_export() {
+    let dot = create_app().schedule_plan().unwrap().to_dot();
+
+    assert!(dot.starts_with("digraph schedule {\n"));
+    assert!(dot.contains("subgraph \"cluster_update_0\" {"));
+    assert!(dot.contains("label=\"update: schedule_export::MainScheduler\";"));
+    assert!(dot.contains("label=\"limnus_default_stages::Update (Parallel)\";"));
+    // stages in order, and the ordering constraint between the systems
+    assert!(dot.contains("\"update_0_0\" -> \"update_0_1\" [style=bold];"));
+    assert!(dot.contains("\"update_0_0_0\" -> \"update_0_0_1\";"));
+    assert!(dot.contains(
+        "[shape=box, label=\"schedule_export::apply_hits\\nreads message schedule_export::Hit\\nwrites resource schedule_export::Score\"];"
+    ));
+    assert!(dot.contains("subgraph \"cluster_unscheduled_0\" {"));
+    assert!(dot.ends_with("}\n"));
+}
+
+#[test]
+fn unsorted_constraints_are_reported() {
+    let mut app = create_app();
+    app.add_system(Update, show_score.after("missing"));
+
+    assert!(app.schedule_plan().is_err());
+}
@@ -0,0 +1,166 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_default_stages::{Shutdown, Update};
+use limnus_local_resource::prelude::LocalResource;
+use limnus_resource::prelude::Resource;
+use limnus_scheduler::Scheduler;
+use limnus_stage::Stages;
+use limnus_system_params::Re;
+use limnus_system_state::State;
+use std::any::TypeId;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+struct StageScheduler(TypeId);
+
+impl Scheduler for StageScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        stages.get_by_id(&self.0).expect("stage missing").run(state);
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Log(Arc<Mutex<Vec<&'static str>>>);
+
+impl Log {
+    fn push(&self, entry: &'static str) {
+        self.0.lock().unwrap().push(entry);
+    }
+
+    fn entries(&self) -> Vec<&'static str> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[derive(Debug, Resource)]
+struct EarlyResource(Log);
+
+impl Drop for EarlyResource {
+    fn drop(&mut self) {
+        self.0.push("drop early resource");
+    }
+}
+
+#[derive(Debug, Resource)]
+struct LateResource(Log);
+
+impl Drop for LateResource {
+    fn drop(&mut self) {
+        self.0.push("drop late resource");
+    }
+}
+
+#[derive(Debug, LocalResource)]
+struct DeviceLocalResource(Log);
+
+impl Drop for DeviceLocalResource {
+    fn drop(&mut self) {
+        self.0.push("drop local resource");
+    }
+}
+
+struct FirstPlugin(Log);
+
+impl Plugin for FirstPlugin {
+    fn cleanup(&self, app: &mut App) {
+        assert!(app.has_resource::<EarlyResource>());
+        self.0.push("cleanup first plugin");
+    }
+}
+
+struct SecondPlugin(Log);
+
+impl Plugin for SecondPlugin {
+    fn cleanup(&self, _app: &mut App) {
+        self.0.push("cleanup second plugin");
+    }
+}
+
+fn on_update(resource: Re<LateResource>) {
+    resource.0.push("update");
+}
+
+fn on_shutdown(resource: Re<LateResource>) {
+    resource.0.push("shutdown stage");
+}
+
+fn create_app(log: &Log) -> App {
+    let mut app = App::new();
+    app.add_stage::<Update>();
+    app.add_stage::<Shutdown>();
+    app.add_scheduler(StageScheduler(TypeId::of::<Update>()));
+    app.add_shutdown_scheduler(StageScheduler(TypeId::of::<Shutdown>()));
+    app.insert_resource(EarlyResource(log.clone()));
+    app.insert_local_resource(DeviceLocalResource(log.clone()));
+    app.insert_resource(LateResource(log.clone()));
+    app.add_system(Update, on_update);
+    app.add_system(Shutdown, on_shutdown);
+    app.add_plugins((FirstPlugin(log.clone()), SecondPlugin(log.clone())));
+    app
+}
+
+#[test]
+fn shutdown_order() {
+    let log = Log::default();
+    let mut app = create_app(&log);
+
+    app.update();
+    assert_eq!(app.shutdown(), AppReturnValue::Value(0));
+
+    assert_eq!(
+        log.entries(),
+        [
+            "update",
+            "shutdown stage",
+            "cleanup second plugin",
+            "cleanup first plugin",
+            "drop local resource",
+            "drop late resource",
+            "drop early resource",
+        ]
+    );
+}
+
+#[test]
+fn shutdown_only_once() {
+    let log = Log::default();
+    let mut app = create_app(&log);
+    app.insert_resource(ApplicationExit {
+        value: AppReturnValue::Value(7),
+    });
+
+    app.update();
+    assert_eq!(app.shutdown(), AppReturnValue::Value(7));
+    let entries_after_shutdown = log.entries();
+
+    app.update();
+    assert_eq!(app.shutdown(), AppReturnValue::Value(7));
+    assert_eq!(log.entries(), entries_after_shutdown);
+}
+
+#[test]
+fn shutdown_before_running_skips_shutdown_stage() {
+    let log = Log::default();
+    let mut app = create_app(&log);
+
+    assert_eq!(app.shutdown(), AppReturnValue::Value(0));
+
+    assert_eq!(log.entries()[0], "cleanup second plugin");
+}
+
+#[test]
+fn headless_runner_returns_after_shutdown() {
+    let log = Log::default();
+    let mut app = create_app(&log);
+    app.add_plugins(HeadlessRunnerPlugin::new(
+        HeadlessRunnerSettings::new().with_max_frames(2),
+    ));
+
+    assert_eq!(app.run(), AppReturnValue::Value(0));
+
+    assert_eq!(log.entries()[..3], ["update", "update", "shutdown stage"]);
+    assert_eq!(log.entries().last(), Some(&"drop early resource"));
+}
@@ -0,0 +1,79 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_default_stages::{Startup, Update};
+use limnus_resource::prelude::Resource;
+use limnus_scheduler::Scheduler;
+use limnus_stage::Stages;
+use limnus_system_params::{Re, ReM};
+use limnus_system_state::State;
+use std::any::TypeId;
+
+#[derive(Debug)]
+struct StageScheduler(TypeId);
+
+impl Scheduler for StageScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        stages.get_by_id(&self.0).expect("stage missing").run(state);
+    }
+}
+
+#[derive(Debug, Resource)]
+struct Device;
+
+#[derive(Debug, Default, Resource)]
+struct Counters {
+    startup: u32,
+    update: u32,
+    update_before_startup: bool,
+}
+
+/// Is not initialized until the `Device` resource has been inserted.
+struct DevicePlugin;
+
+impl Plugin for DevicePlugin {
+    fn is_initialized(&self, app: &App) -> bool {
+        app.has_resource::<Device>()
+    }
+}
+
+fn on_startup(mut counters: ReM<Counters>, _device: Re<Device>) {
+    counters.startup += 1;
+}
+
+fn on_update(mut counters: ReM<Counters>) {
+    if counters.startup == 0 {
+        counters.update_before_startup = true;
+    }
+    counters.update += 1;
+}
+
+#[test]
+fn startup_runs_once_when_plugins_are_ready() {
+    let mut app = App::new();
+    app.add_stage::<Startup>();
+    app.add_stage::<Update>();
+    app.add_startup_scheduler(StageScheduler(TypeId::of::<Startup>()));
+    app.add_scheduler(StageScheduler(TypeId::of::<Update>()));
+    app.insert_resource(Counters::default());
+    app.add_system(Startup, on_startup);
+    app.add_system(Update, on_update);
+    app.add_plugins(DevicePlugin);
+
+    app.update();
+    app.update();
+    assert_eq!(app.resource::<Counters>().startup, 0);
+    assert_eq!(app.resource::<Counters>().update, 0);
+
+    app.insert_resource(Device);
+    app.update();
+    app.update();
+    app.update();
+
+    let counters = app.resource::<Counters>();
+    assert_eq!(counters.startup, 1);
+    assert_eq!(counters.update, 3);
+    assert!(!counters.update_before_startup);
+}
@@ -0,0 +1,139 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_resource::prelude::Resource;
+use limnus_stage::ExecutorKind;
+use limnus_system::{IntoSystem, SystemErrorHandler};
+use limnus_system_params::{Re, ReM};
+use limnus_system_state::State;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Resource)]
+struct Health(i32);
+
+#[derive(Debug)]
+struct Dead;
+
+impl Display for Dead {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "out of health")
+    }
+}
+
+impl std::error::Error for Dead {}
+
+fn damage(mut health: ReM<Health>) -> Result<(), Dead> {
+    health.0 -= 10;
+    if health.0 <= 0 { Err(Dead) } else { Ok(()) }
+}
+
+fn check(health: Re<Health>) -> Result<(), String> {
+    if health.0 < 0 {
+        return Err(format!("negative health {}", health.0));
+    }
+    Ok(())
+}
+
+fn create_app(health: i32) -> App {
+    let mut app = update_app();
+    app.insert_resource(Health(health));
+    app
+}
+
+fn collect_errors(app: &mut App) -> Arc<Mutex<Vec<String>>> {
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let handler_errors = Arc::clone(&errors);
+    app.set_system_error_handler(SystemErrorHandler::custom(move |error| {
+        handler_errors.lock().unwrap().push(error.to_string());
+    }));
+    errors
+}
+
+#[test]
+fn custom_handler_gets_system_and_stage() {
+    let mut app = create_app(15);
+    let errors = collect_errors(&mut app);
+    app.add_system(Update, damage);
+
+    app.update();
+    assert!(errors.lock().unwrap().is_empty());
+    app.update();
+
+    assert_eq!(
+        *errors.lock().unwrap(),
+        [
+            "system `system_errors::damage` in stage `limnus_default_stages::Update` failed: out of health"
+        ]
+    );
+}
+
+#[test]
+fn default_handler_logs_and_continues() {
+    let mut app = create_app(5);
+    app.add_system(Update, damage);
+
+    app.update();
+    app.update();
+
+    assert_eq!(app.resource::<Health>().0, -15);
+}
+
+#[test]
+#[should_panic(expected = "system `system_errors::check` in stage")]
+fn panic_handler() {
+    let mut app = create_app(-1);
+    app.set_system_error_handler(SystemErrorHandler::Panic);
+    app.add_system(Update, check);
+
+    app.update();
+}
+
+#[test]
+fn exclusive_and_conditional_systems_can_fail() {
+    fn reset(state: &mut State) -> Result<(), String> {
+        state.resource_mut::<Health>().unwrap().0 = 100;
+        Err("reset".to_string())
+    }
+    fn never() -> bool {
+        false
+    }
+
+    let mut app = create_app(0);
+    let errors = collect_errors(&mut app);
+    app.add_system(Update, reset.label("reset"));
+    app.add_system(Update, check.run_if(never).after("reset"));
+
+    app.update();
+
+    assert_eq!(
+        *errors.lock().unwrap(),
+        [
+            "system `system_errors::exclusive_and_conditional_systems_can_fail::reset` in stage `limnus_default_stages::Update` failed: reset"
+        ]
+    );
+}
+
+#[test]
+fn parallel_errors_are_handled_in_serial_order() {
+    let mut app = create_app(-5);
+    let errors = collect_errors(&mut app);
+    app.set_stage_executor(Update, ExecutorKind::Parallel);
+    app.add_system(Update, check);
+    app.add_system(Update, |_health: Re<Health>| -> Result<(), String> {
+        Err("second".to_string())
+    });
+
+    app.update();
+
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].ends_with("negative health -5"));
+    assert!(errors[1].ends_with("second"));
+}
@@ -0,0 +1,136 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+mod common;
+
+use common::update_app;
+use limnus_app::prelude::*;
+use limnus_default_stages::Update;
+use limnus_macros::SystemParam;
+use limnus_message::prelude::Message;
+use limnus_resource::prelude::Resource;
+use limnus_system::{Access, SystemParam as _};
+use limnus_system_params::{Msg, MsgM, Re, ReM};
+
+#[derive(Debug, Resource)]
+struct Score(u32);
+
+#[derive(Debug, Resource)]
+struct Bonus(u32);
+
+#[derive(Debug, Default, Resource)]
+struct Log {
+    entries: Vec<u32>,
+}
+
+#[derive(Debug, Message)]
+struct Scored;
+
+#[derive(SystemParam)]
+struct Scoring<'a> {
+    score: ReM<'a, Score>,
+    bonus: Option<Re<'a, Bonus>>,
+}
+
+impl Scoring<'_> {
+    fn add(&mut self, points: u32) {
+        self.score.0 += points * self.bonus.as_ref().map_or(1, |bonus| bonus.0);
+    }
+}
+
+#[derive(SystemParam)]
+struct Game<'a> {
+    scoring: Scoring<'a>,
+    log: ReM<'a, Log>,
+}
+
+// Never fetched, since the fields conflict
+#[allow(dead_code)]
+#[derive(SystemParam)]
+struct Events<'a>(MsgM<'a, Scored>, Msg<'a, Scored>);
+
+fn play(mut game: Game) {
+    game.scoring.add(2);
+    let score = game.scoring.score.0;
+    game.log.entries.push(score);
+}
+
+fn create_app() -> App {
+    let mut app = update_app();
+    app.insert_resource(Score(0));
+    app.insert_resource(Log::default());
+    app
+}
+
+#[test]
+fn nested_param_structs() {
+    let mut app = create_app();
+    app.add_system(Update, play);
+
+    app.update();
+    app.insert_resource(Bonus(10));
+    app.update();
+
+    assert_eq!(app.resource::<Log>().entries, [2, 22]);
+}
+
+#[test]
+fn access_of_fields_is_combined() {
+    let mut access = Access::default();
+    Game::access(&mut access);
+
+    assert_eq!(access.resources().write_names().len(), 2);
+    assert_eq!(access.resources().read_names().len(), 1);
+    assert!(access.conflicts().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "has conflicting params: message")]
+fn conflicting_fields_are_rejected() {
+    fn uses_events(_events: Events) {}
+
+    let mut app = create_app();
+    app.add_system(Update, uses_events);
+}
+
+#[test]
+#[should_panic(expected = "has conflicting params: resource")]
+fn conflicts_with_other_params_are_rejected() {
+    fn reads_score(_game: Game, _score: Re<Score>) {}
+
+    let mut app = create_app();
+    app.add_system(Update, reads_score);
+}
+
+#[test]
+#[allow(clippy::too_many_arguments)]
+fn more_than_eight_params() {
+    #[derive(Debug, Resource)]
+    struct A;
+
+    fn twelve(
+        _a1: Re<A>,
+        _a2: Re<A>,
+        _a3: Re<A>,
+        _a4: Re<A>,
+        _a5: Re<A>,
+        _a6: Re<A>,
+        _a7: Re<A>,
+        _a8: Re<A>,
+        _a9: Re<A>,
+        _a10: Re<A>,
+        _bonus: Option<Re<Bonus>>,
+        mut log: ReM<Log>,
+    ) {
+        log.entries.push(12);
+    }
+
+    let mut app = create_app();
+    app.insert_resource(A);
+    app.add_system(Update, twelve);
+
+    app.update();
+
+    assert_eq!(app.resource::<Log>().entries, [12]);
+}
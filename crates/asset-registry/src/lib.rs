@@ -10,12 +10,14 @@ use chunk_reader::get_platform_reader;
 use limnus_app::prelude::*;
 use limnus_assets::prelude::*;
 use limnus_assets_loader::ResourceStorage;
-use limnus_assets_loader::{AssetLoaderRegistry, LoadError, WrappedAssetLoaderRegistry};
+use limnus_assets_loader::{
+    AssetLoaderRegistry, AssetLoaderRegistryPlugin, LoadError, WrappedAssetLoaderRegistry,
+};
 use limnus_default_stages::First;
-use limnus_loader::{Blob, LoaderReceiver, LoaderSender, load};
+use limnus_loader::{Blob, LoaderPlugin, LoaderReceiver, LoaderSender, load};
 use limnus_local_resource::LocalResourceStorage;
 use limnus_resource::prelude::Resource;
-use limnus_system_params::{LoReAll, Re, ReAll, ReM};
+use limnus_system_state::State;
 use message_channel::{Channel, Receiver, Sender};
 use std::any::TypeId;
 use std::collections::HashMap;
@@ -108,13 +110,18 @@ impl AssetRegistry {
         resources: &mut ResourceStorage,
         local_resources: &mut LocalResourceStorage,
     ) -> Result<(), LoadError> {
-        self.infos.get_mut(&id).unwrap().phase = Phase::Defined;
-        self.converters
+        self.define(id)
             .lock()
             .unwrap()
             .convert_and_insert(id, octets, resources, local_resources)
     }
 
+    /// Marks the asset as defined and returns the converters that should convert its blob.
+    fn define(&mut self, id: RawWeakId) -> Arc<Mutex<AssetLoaderRegistry>> {
+        self.infos.get_mut(&id).unwrap().phase = Phase::Defined;
+        Arc::clone(&self.converters)
+    }
+
     pub fn asset_id_dropped<A: Asset>(&mut self, id: Id<A>) {
         self.infos.remove(&(&id).into());
         self.id_assigner.remove(id);
@@ -135,23 +142,30 @@ impl Plugin for AssetRegistryPlugin {
         }
         app.add_system(First, tick);
     }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![
+            PluginDependency::plugin::<LoaderPlugin>(),
+            PluginDependency::plugin::<AssetLoaderRegistryPlugin>(),
+        ]
+    }
 }
 
-fn tick(
-    loader_receiver: Re<LoaderReceiver>,
-    mut asset_container: ReM<AssetRegistry>,
-    mut mut_access_to_resources: ReAll,
-    mut mut_access_to_local_resources: LoReAll,
-) {
-    if let Some(blob) = loader_receiver.receiver.try_recv() {
+fn tick(state: &mut State) -> Result<(), LoadError> {
+    if let Some(blob) = state.resource::<LoaderReceiver>().receiver.try_recv() {
         debug!("loaded {:?}, starting conversion", blob);
-        asset_container
-            .blob_loaded(
-                blob.id,
-                &blob.content,
-                &mut mut_access_to_resources,
-                &mut mut_access_to_local_resources,
-            )
-            .expect("couldn't convert");
+        // The registry is a resource itself, so it can not stay borrowed while the converted asset is inserted
+        let converters = state
+            .resource_mut::<AssetRegistry>()
+            .expect("asset registry is missing")
+            .define(blob.id);
+        let (resources, local_resources) = state.resources_and_local_resources_mut();
+        converters.lock().unwrap().convert_and_insert(
+            blob.id,
+            &blob.content,
+            resources,
+            local_resources,
+        )?;
     }
+    Ok(())
 }
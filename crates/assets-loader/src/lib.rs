@@ -9,7 +9,7 @@ pub use limnus_resource::ResourceStorage;
 use limnus_resource::prelude::Resource;
 use std::any::{TypeId, type_name};
 use std::collections::HashMap;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
 use std::io;
 use std::io::Error;
 use std::sync::{Arc, Mutex};
@@ -28,6 +28,29 @@ pub enum ConversionError {
     IoError(io::Error),
 }
 
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingLoader(id) => write!(f, "no asset loader is registered for asset {id}"),
+            Self::ConversionError(err) => write!(f, "asset conversion failed: {err}"),
+            Self::Downcast => write!(f, "asset loader got the wrong asset type"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongFormat => write!(f, "wrong format"),
+            Self::IoError(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
 impl From<ConversionError> for LoadError {
     fn from(err: ConversionError) -> Self {
         Self::ConversionError(err)
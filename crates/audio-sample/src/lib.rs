@@ -2,12 +2,13 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
-use limnus_app::prelude::{App, Plugin};
+use limnus_app::prelude::{App, Plugin, PluginDependency};
 use limnus_asset_registry::AssetRegistry;
 use limnus_assets::Assets;
 use limnus_assets::prelude::{AssetName, RawWeakId};
 use limnus_assets_loader::{
-    AssetLoader, ConversionError, ResourceStorage, WrappedAssetLoaderRegistry,
+    AssetLoader, AssetLoaderRegistryPlugin, ConversionError, ResourceStorage,
+    WrappedAssetLoaderRegistry,
 };
 use limnus_audio_mixer::StereoSample;
 use limnus_local_resource::LocalResourceStorage;
@@ -60,6 +61,10 @@ impl Plugin for AudioSamplePlugin {
         }
         app.insert_resource(Assets::<StereoSample>::default());
     }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![PluginDependency::plugin::<AssetLoaderRegistryPlugin>()]
+    }
 }
 
 #[derive(Default)]
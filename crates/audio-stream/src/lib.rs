@@ -4,9 +4,10 @@
  */
 use cpal::traits::{DeviceTrait, StreamTrait};
 use cpal::{Device, StreamConfig};
-use limnus_app::prelude::{App, Plugin};
+use limnus_app::prelude::{App, Plugin, PluginDependency};
+use limnus_audio_device::AudioDevicePlugin;
 use limnus_audio_device::low_level::Audio;
-use limnus_audio_mixer::AudioMixer;
+use limnus_audio_mixer::{AudioMixer, AudioMixerPlugin};
 use limnus_local_resource::prelude::LocalResource;
 use std::fmt::Debug;
 use std::sync::{Arc, Mutex};
@@ -66,4 +67,11 @@ impl Plugin for AudioStreamPlugin {
         let audio_stream = AudioStream { stream };
         app.insert_local_resource(audio_stream);
     }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![
+            PluginDependency::plugin::<AudioDevicePlugin>(),
+            PluginDependency::plugin::<AudioMixerPlugin>(),
+        ]
+    }
 }
@@ -0,0 +1,17 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::InputMessage;
+use crate::button::ButtonState;
+use crate::key::KeyCode;
+use limnus_system_params::Msg;
+
+/// Condition that is true if `key_code` was pressed since the previous frame.
+pub fn key_pressed(key_code: KeyCode) -> impl Fn(Msg<'static, InputMessage>) -> bool + 'static {
+    move |messages| {
+        messages.iter_previous().any(|message| {
+            matches!(message, InputMessage::KeyboardInput(ButtonState::Pressed, key) if *key == key_code)
+        })
+    }
+}
@@ -7,9 +7,12 @@ use crate::key::KeyCode;
 use limnus_message::Message;
 
 mod button;
+mod conditions;
 mod key;
 pub mod prelude;
 
+pub use conditions::key_pressed;
+
 #[derive(Debug, Copy, Clone)]
 pub enum InputMessage {
     KeyboardInput(ButtonState, KeyCode),
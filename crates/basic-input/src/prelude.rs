@@ -2,4 +2,4 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
-pub use {crate::InputMessage, crate::button::*, crate::key::*};
+pub use {crate::InputMessage, crate::button::*, crate::key::*, crate::key_pressed};
@@ -4,12 +4,16 @@
  */
 
 pub mod prelude;
+mod time;
+
+pub use time::Time;
 
 use limnus_app::prelude::{App, Plugin};
 use limnus_default_stages::First;
 use limnus_local_resource::LocalResource;
 use limnus_macros::{LocalResource, Resource};
 use limnus_resource::Resource;
+use limnus_system::IntoSystem;
 use limnus_system_params::{LoRe, ReM};
 use monotonic_time_rs::{Millis, MonotonicClock, create_monotonic_clock};
 use std::fmt::{Debug, Formatter};
@@ -30,8 +34,12 @@ pub struct MonotonicTime {
     pub time: Millis,
 }
 
-fn update_time(clock: LoRe<Clock>, mut time: ReM<MonotonicTime>) {
+/// Label of the system in `First` that updates [`MonotonicTime`] and [`Time`].
+pub const UPDATE_TIME: &str = "update_time";
+
+fn update_time(clock: LoRe<Clock>, mut time: ReM<MonotonicTime>, mut frame_time: ReM<Time>) {
     time.time = clock.clock.now();
+    frame_time.advance_to(time.time);
 }
 
 pub struct ClockPlugin;
@@ -44,7 +52,8 @@ impl Plugin for ClockPlugin {
             clock: Box::new(clock),
         });
         app.insert_resource(MonotonicTime { time: now });
+        app.insert_resource(Time::default());
 
-        app.add_system(First, update_time);
+        app.add_system(First, update_time.label(UPDATE_TIME));
     }
 }
@@ -3,4 +3,4 @@
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
 
-pub use crate::{Clock, MonotonicTime};
+pub use crate::{Clock, MonotonicTime, Time, UPDATE_TIME};
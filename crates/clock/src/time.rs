@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_macros::Resource;
+use limnus_resource::Resource;
+use monotonic_time_rs::Millis;
+use std::time::Duration;
+
+/// Frame timing, updated by `ClockPlugin` at the start of each update.
+///
+/// There are two timelines. The real one follows the clock. The virtual one is what
+/// gameplay and animations should use: it stands still while paused, and runs slower or faster
+/// with the [`Self::scale`].
+#[derive(Debug, Clone, Resource)]
+pub struct Time {
+    delta: Duration,
+    elapsed: Duration,
+    real_delta: Duration,
+    real_elapsed: Duration,
+    frame_count: u64,
+    paused: bool,
+    scale: f64,
+    last_time: Option<Millis>,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self {
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            real_delta: Duration::ZERO,
+            real_elapsed: Duration::ZERO,
+            frame_count: 0,
+            paused: false,
+            scale: 1.0,
+            last_time: None,
+        }
+    }
+}
+
+impl Time {
+    /// The virtual time since the previous frame. Zero while paused and in the first frame.
+    #[must_use]
+    pub const fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    #[must_use]
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// The virtual time since the first frame.
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    #[must_use]
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// The real time since the previous frame, also while paused.
+    #[must_use]
+    pub const fn real_delta(&self) -> Duration {
+        self.real_delta
+    }
+
+    /// The real time since the first frame.
+    #[must_use]
+    pub const fn real_elapsed(&self) -> Duration {
+        self.real_elapsed
+    }
+
+    /// The number of frames so far, one in the first frame.
+    #[must_use]
+    pub const fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Stops the virtual time from the next frame. The real time keeps running.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    #[must_use]
+    pub const fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// How fast the virtual time runs compared to the real time. `1.0` by default.
+    #[must_use]
+    pub const fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Sets how fast the virtual time runs from the next frame, for example `0.5` for slow motion.
+    ///
+    /// # Panics
+    /// If `scale` is negative or not a number.
+    pub fn set_scale(&mut self, scale: f64) {
+        assert!(
+            scale.is_finite() && scale >= 0.0,
+            "time scale must be zero or positive, got {scale}"
+        );
+        self.scale = scale;
+    }
+
+    /// Moves both timelines to `now`. The first call only starts the clock.
+    pub fn advance_to(&mut self, now: Millis) {
+        self.real_delta = self
+            .last_time
+            .and_then(|last_time| now.checked_duration_since(last_time))
+            .unwrap_or_default();
+        self.last_time = Some(now);
+        self.real_elapsed += self.real_delta;
+
+        self.delta = if self.paused {
+            Duration::ZERO
+        } else {
+            self.real_delta.mul_f64(self.scale)
+        };
+        self.elapsed += self.delta;
+        self.frame_count += 1;
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_clock::ClockPlugin;
+use limnus_clock::prelude::*;
+use limnus_default_stages::{First, Update};
+use limnus_resource::prelude::Resource;
+use limnus_scheduler::Scheduler;
+use limnus_stage::{StageId, Stages};
+use limnus_system_params::{Re, ReM};
+use limnus_system_state::State;
+use monotonic_time_rs::{Millis, MonotonicClock};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+#[derive(Debug)]
+struct FrameScheduler;
+
+impl Scheduler for FrameScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        for stage_id in [StageId::of::<First>(), StageId::of::<Update>()] {
+            stages
+                .get_by_stage_id(&stage_id)
+                .expect("stage missing")
+                .run(state);
+        }
+    }
+}
+
+/// A clock that only moves when the test says so.
+struct ManualClock(Arc<AtomicU64>);
+
+impl MonotonicClock for ManualClock {
+    fn now(&self) -> Millis {
+        Millis::new(self.0.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+struct Position(f32);
+
+fn animate(time: Re<Time>, mut position: ReM<Position>) {
+    position.0 += 10.0 * time.delta_secs();
+}
+
+/// The app and the function that moves the clock forward, in milliseconds.
+fn create_app() -> (App, impl Fn(u64)) {
+    let now = Arc::new(AtomicU64::new(5_000));
+    let mut app = App::new();
+    app.add_stage::<First>();
+    app.add_stage::<Update>();
+    app.add_plugins(ClockPlugin);
+    app.insert_local_resource(Clock {
+        clock: Box::new(ManualClock(Arc::clone(&now))),
+    });
+    app.add_scheduler(FrameScheduler);
+    app.insert_resource(Position::default());
+    app.add_system(Update, animate);
+
+    let advance = move |millis| {
+        now.fetch_add(millis, Ordering::Relaxed);
+    };
+    (app, advance)
+}
+
+#[test]
+fn first_frame_starts_the_clock() {
+    let (mut app, _advance) = create_app();
+    app.update();
+
+    let time = app.resource::<Time>();
+    assert_eq!(time.frame_count(), 1);
+    assert_eq!(time.delta(), Duration::ZERO);
+    assert_eq!(time.real_elapsed(), Duration::ZERO);
+}
+
+#[test]
+fn delta_and_elapsed_follow_the_clock() {
+    let (mut app, advance) = create_app();
+    app.update();
+    advance(100);
+    app.update();
+    advance(250);
+    app.update();
+
+    let time = app.resource::<Time>();
+    assert_eq!(time.frame_count(), 3);
+    assert_eq!(time.delta(), Duration::from_millis(250));
+    assert_eq!(time.elapsed(), Duration::from_millis(350));
+    assert_eq!(time.real_elapsed(), Duration::from_millis(350));
+    assert!((app.resource::<Position>().0 - 3.5).abs() < 1e-4);
+}
+
+#[test]
+fn pause_and_scale_only_change_the_virtual_time() {
+    let (mut app, advance) = create_app();
+    app.update();
+
+    app.resource_mut::<Time>().set_scale(0.5);
+    advance(200);
+    app.update();
+    assert_eq!(app.resource::<Time>().delta(), Duration::from_millis(100));
+
+    app.resource_mut::<Time>().pause();
+    advance(200);
+    app.update();
+    let time = app.resource::<Time>();
+    assert!(time.is_paused());
+    assert_eq!(time.delta(), Duration::ZERO);
+    assert_eq!(time.real_delta(), Duration::from_millis(200));
+    assert_eq!(time.elapsed(), Duration::from_millis(100));
+    assert_eq!(time.real_elapsed(), Duration::from_millis(400));
+    assert!((app.resource::<Position>().0 - 1.0).abs() < 1e-4);
+
+    app.resource_mut::<Time>().unpause();
+    advance(200);
+    app.update();
+    assert_eq!(app.resource::<Time>().elapsed(), Duration::from_millis(200));
+}
+
+#[test]
+#[should_panic(expected = "time scale must be zero or positive")]
+fn scale_must_not_be_negative() {
+    Time::default().set_scale(-1.0);
+}
@@ -0,0 +1,218 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_resource::prelude::Resource;
+use monotonic_time_rs::Millis;
+use std::any::type_name;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Marks the [`FixedTime`] of the fixed scheduler that the `DefaultSchedulersPlugin` adds.
+#[derive(Debug)]
+pub struct Fixed;
+
+/// What a [`crate::FixedScheduler`] does when it has fallen more than a few steps behind,
+/// for example because the fixed stages take longer to run than the step they simulate.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CatchUpPolicy {
+    /// Runs as many steps as needed. If the steps are slower than real time, each update needs
+    /// more steps than the one before.
+    Unlimited,
+    /// Runs at most `max_steps` in an update and drops the time that is left, so the simulation
+    /// slows down instead of falling further behind.
+    DropExcess { max_steps: u32 },
+    /// Runs at most `max_steps` in an update and keeps the time that is left for the following updates.
+    Carry { max_steps: u32 },
+}
+
+impl Default for CatchUpPolicy {
+    fn default() -> Self {
+        Self::DropExcess { max_steps: 4 }
+    }
+}
+
+/// The fixed timestep of a [`crate::FixedScheduler`], and how far it has come.
+///
+/// Each fixed scheduler has its own `FixedTime`, told apart by the marker type `T`,
+/// so `Re<FixedTime<Physics>>` is the time of the scheduler that was created with
+/// `FixedScheduler::<Physics>::new`. The default scheduler uses [`Fixed`].
+///
+/// Real time is accumulated with nanosecond precision, so a step that is not a whole number of
+/// milliseconds, like the 16.67 ms of 60 Hz, does not drift.
+pub struct FixedTime<T = Fixed> {
+    step: Duration,
+    catch_up: CatchUpPolicy,
+    tick: u64,
+    elapsed: Duration,
+    overstep: Duration,
+    last_time: Option<Millis>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> Resource for FixedTime<T> {}
+
+impl<T> Debug for FixedTime<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FixedTime")
+            .field("marker", &type_name::<T>())
+            .field("step", &self.step)
+            .field("catch_up", &self.catch_up)
+            .field("tick", &self.tick)
+            .field("elapsed", &self.elapsed)
+            .field("overstep", &self.overstep)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Clone for FixedTime<T> {
+    fn clone(&self) -> Self {
+        Self {
+            step: self.step,
+            catch_up: self.catch_up,
+            tick: self.tick,
+            elapsed: self.elapsed,
+            overstep: self.overstep,
+            last_time: self.last_time,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for FixedTime<T> {
+    /// 60 Hz, with the default [`CatchUpPolicy`].
+    fn default() -> Self {
+        Self::from_hz(60.0)
+    }
+}
+
+impl<T> FixedTime<T> {
+    /// # Panics
+    /// If `step` is zero.
+    #[must_use]
+    pub fn from_step(step: Duration) -> Self {
+        assert!(!step.is_zero(), "fixed time step must be longer than zero");
+        Self {
+            step,
+            catch_up: CatchUpPolicy::default(),
+            tick: 0,
+            elapsed: Duration::ZERO,
+            overstep: Duration::ZERO,
+            last_time: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// # Panics
+    /// If `hz` is not a positive number, or so high that the step is shorter than a nanosecond.
+    #[must_use]
+    pub fn from_hz(hz: f64) -> Self {
+        assert!(
+            hz.is_finite() && hz > 0.0,
+            "fixed time rate must be a positive number, got {hz}"
+        );
+        // Rounded down to whole nanoseconds, so `hz` steps never take longer than a second
+        Self::from_step(Duration::from_nanos((1_000_000_000.0 / hz) as u64))
+    }
+
+    #[must_use]
+    pub const fn with_catch_up(mut self, catch_up: CatchUpPolicy) -> Self {
+        self.catch_up = catch_up;
+        self
+    }
+
+    /// The length of each fixed step.
+    #[must_use]
+    pub const fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Changes the step. Time that has already been accumulated is kept.
+    ///
+    /// # Panics
+    /// If `step` is zero.
+    pub fn set_step(&mut self, step: Duration) {
+        assert!(!step.is_zero(), "fixed time step must be longer than zero");
+        self.step = step;
+    }
+
+    #[must_use]
+    pub fn hz(&self) -> f64 {
+        1.0 / self.step.as_secs_f64()
+    }
+
+    #[must_use]
+    pub const fn catch_up(&self) -> CatchUpPolicy {
+        self.catch_up
+    }
+
+    pub fn set_catch_up(&mut self, catch_up: CatchUpPolicy) {
+        self.catch_up = catch_up;
+    }
+
+    /// The number of steps that have been run. While the fixed stages run, the index of the current step, starting at one.
+    #[must_use]
+    pub const fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// The simulated time, one [`Self::step`] for each tick.
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Real time that has been accumulated but not yet simulated, less than a step unless the
+    /// [`CatchUpPolicy::Carry`] policy has carried time over.
+    #[must_use]
+    pub const fn overstep(&self) -> Duration {
+        self.overstep
+    }
+
+    /// How far real time has come towards the next step, from `0.0` to `1.0`.
+    ///
+    /// Render systems use it to interpolate between the two latest fixed steps.
+    #[must_use]
+    pub fn alpha(&self) -> f32 {
+        (self.overstep.as_secs_f64() / self.step.as_secs_f64()).min(1.0) as f32
+    }
+
+    /// Adds the real time since the previous call, and returns how many steps to run, according to the [`CatchUpPolicy`].
+    ///
+    /// The first call only remembers the time.
+    pub(crate) fn accumulate(&mut self, now: Millis) -> u32 {
+        let delta = self
+            .last_time
+            .and_then(|last_time| now.checked_duration_since(last_time))
+            .unwrap_or_default();
+        self.last_time = Some(now);
+        self.overstep += delta;
+
+        let steps_behind = self.overstep.as_nanos() / self.step.as_nanos();
+        match self.catch_up {
+            CatchUpPolicy::Unlimited => u32::try_from(steps_behind).unwrap_or(u32::MAX),
+            CatchUpPolicy::Carry { max_steps } => {
+                u32::try_from(steps_behind).map_or(max_steps, |steps| steps.min(max_steps))
+            }
+            CatchUpPolicy::DropExcess { max_steps } => {
+                let steps =
+                    u32::try_from(steps_behind).map_or(max_steps, |steps| steps.min(max_steps));
+                if u128::from(steps) < steps_behind {
+                    // Keeps the part of a step that is left, so the alpha still moves on smoothly
+                    let remainder = self.overstep.as_nanos() % self.step.as_nanos();
+                    self.overstep = self.step * steps
+                        + Duration::from_nanos(u64::try_from(remainder).unwrap_or(u64::MAX));
+                }
+                steps
+            }
+        }
+    }
+
+    /// Called before each run of the fixed stages.
+    pub(crate) fn start_step(&mut self) {
+        self.tick += 1;
+        self.elapsed += self.step;
+        self.overstep = self.overstep.saturating_sub(self.step);
+    }
+}
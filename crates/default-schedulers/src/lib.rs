@@ -3,126 +3,234 @@
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
 
-use limnus_app::prelude::{App, Plugin};
-use limnus_clock::MonotonicTime;
+mod fixed_time;
+
+pub use fixed_time::{CatchUpPolicy, Fixed, FixedTime};
+
+use limnus_app::prelude::{App, Plugin, PluginDependency};
+use limnus_clock::{ClockPlugin, MonotonicTime, UPDATE_TIME};
 use limnus_default_stages::{
-    First, FixedFirst, FixedPostUpdate, FixedPreUpdate, FixedUpdate, PostUpdate, PreUpdate,
-    RenderFirst, RenderPostUpdate, RenderPreUpdate, RenderUpdate, Update,
+    First, FixedFirst, FixedPostUpdate, FixedPreUpdate, FixedUpdate, PostStartup, PostUpdate,
+    PreStartup, PreUpdate, RenderFirst, RenderPostUpdate, RenderPreUpdate, RenderUpdate, Shutdown,
+    Startup, Update,
 };
-use limnus_resource::prelude::Resource;
-use limnus_scheduler::Scheduler;
+use limnus_scheduler::{Scheduler, StageList};
 use limnus_stage::Stages;
+use limnus_system::IntoSystem;
 use limnus_system_params::MsgAll;
 use limnus_system_state::State;
-use monotonic_time_rs::{Millis, MillisDuration};
-use std::any::TypeId;
+use std::any::type_name;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
 
+/// Runs [`PreStartup`], [`Startup`] and [`PostStartup`] once, before the first update.
 #[derive(Debug)]
-pub struct MainScheduler;
-impl Scheduler for MainScheduler {
-    fn schedule(&self, stages: &Stages, state: &mut State) {
-        let stage_ids = {
-            vec![
-                TypeId::of::<First>(),
-                TypeId::of::<PreUpdate>(),
-                TypeId::of::<Update>(),
-                TypeId::of::<PostUpdate>(),
-            ]
-        };
-
-        for &stage_id in &stage_ids {
-            stages
-                .get_by_id(&stage_id)
-                .expect("stage missing")
-                .run(state);
+pub struct StartupScheduler {
+    stages: StageList,
+}
+
+impl Default for StartupScheduler {
+    fn default() -> Self {
+        Self {
+            stages: StageList::new()
+                .with::<PreStartup>()
+                .with::<Startup>()
+                .with::<PostStartup>(),
         }
     }
 }
 
-#[derive(Debug, Resource)]
-pub struct FixedSchedulerData {
-    pub consumed_up_to_time: Millis,
-    pub ticks_per_second: usize,
+impl Scheduler for StartupScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        self.stages.run(stages, state);
+    }
+
+    fn stage_list(&self) -> Option<&StageList> {
+        Some(&self.stages)
+    }
+
+    fn stage_list_mut(&mut self) -> Option<&mut StageList> {
+        Some(&mut self.stages)
+    }
 }
 
+/// Runs [`First`], [`PreUpdate`], [`Update`] and [`PostUpdate`] on every update.
 #[derive(Debug)]
-pub struct FixedScheduler;
-impl Scheduler for FixedScheduler {
+pub struct MainScheduler {
+    stages: StageList,
+}
+
+impl Default for MainScheduler {
+    fn default() -> Self {
+        Self {
+            stages: StageList::new()
+                .with::<First>()
+                .with::<PreUpdate>()
+                .with::<Update>()
+                .with::<PostUpdate>(),
+        }
+    }
+}
+
+impl Scheduler for MainScheduler {
     fn schedule(&self, stages: &Stages, state: &mut State) {
-        let current_time = { state.resources().fetch::<MonotonicTime>().time };
-
-        let (mut consumed_time, ticks_per_second) = {
-            let data = state.resources().fetch::<FixedSchedulerData>();
-            (data.consumed_up_to_time, data.ticks_per_second)
-        };
-
-        let steps_to_perform = if consumed_time > current_time {
-            // We are ahead
-            let time_ahead = consumed_time - current_time;
-            let exact_steps_ahead = (time_ahead.as_millis() * ticks_per_second as u64) / 1_000;
-            #[allow(clippy::bool_to_int_with_if)]
-            if exact_steps_ahead > 4 { 0 } else { 1 }
-        } else {
-            // We are behind
-            let time_debt = current_time - consumed_time;
-
-            let exact_steps_needed = (time_debt.as_millis() * ticks_per_second as u64) / 1_000;
-
-            // If we need significantly more than 1 step, consider doing 2
-            if exact_steps_needed > 1 { 2 } else { 1 }
-        };
-
-        let stage_ids = {
-            vec![
-                TypeId::of::<FixedFirst>(),
-                TypeId::of::<FixedPreUpdate>(),
-                TypeId::of::<FixedUpdate>(),
-                TypeId::of::<FixedPostUpdate>(),
-            ]
-        };
-
-        let fixed_time_step_ms = 1000 / ticks_per_second;
-
-        for _ in 0..steps_to_perform {
-            for &stage_id in &stage_ids {
-                stages
-                    .get_by_id(&stage_id)
-                    .expect("stage missing")
-                    .run(state);
-            }
-            consumed_time += MillisDuration::from_millis(fixed_time_step_ms as u64);
+        self.stages.run(stages, state);
+    }
+
+    fn stage_list(&self) -> Option<&StageList> {
+        Some(&self.stages)
+    }
+
+    fn stage_list_mut(&mut self) -> Option<&mut StageList> {
+        Some(&mut self.stages)
+    }
+}
+
+/// Runs its stages once for each step of the [`FixedTime<T>`] resource that real time has passed.
+///
+/// Several fixed schedulers, each with its own marker type `T`, stages and [`FixedTime<T>`],
+/// can run at different rates. They run in the order they are added to the app:
+///
+/// ```ignore
+/// struct Physics;
+///
+/// app.add_stage::<PhysicsStep>();
+/// app.insert_resource(FixedTime::<Physics>::from_hz(120.0));
+/// app.add_scheduler(FixedScheduler::<Physics>::new(StageList::new().with::<PhysicsStep>()));
+/// ```
+///
+/// The default one, added by [`DefaultSchedulersPlugin`], runs [`FixedFirst`], [`FixedPreUpdate`],
+/// [`FixedUpdate`] and [`FixedPostUpdate`] at the rate of [`FixedTime<Fixed>`].
+pub struct FixedScheduler<T = Fixed> {
+    stages: StageList,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> FixedScheduler<T> {
+    #[must_use]
+    pub const fn new(stages: StageList) -> Self {
+        Self {
+            stages,
+            marker: PhantomData,
         }
+    }
+}
+
+impl Default for FixedScheduler {
+    fn default() -> Self {
+        Self::new(
+            StageList::new()
+                .with::<FixedFirst>()
+                .with::<FixedPreUpdate>()
+                .with::<FixedUpdate>()
+                .with::<FixedPostUpdate>(),
+        )
+    }
+}
+
+impl<T> Debug for FixedScheduler<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FixedScheduler<{}> {:?}",
+            type_name::<T>(),
+            self.stages.names()
+        )
+    }
+}
 
-        {
-            let fixed_scheduler_data = state.resources_mut().fetch_mut::<FixedSchedulerData>();
-            fixed_scheduler_data.consumed_up_to_time = consumed_time;
+impl<T: 'static> Scheduler for FixedScheduler<T> {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        let now = state.resources().fetch::<MonotonicTime>().time;
+        let steps = fixed_time::<T>(state).accumulate(now);
+
+        for _ in 0..steps {
+            fixed_time::<T>(state).start_step();
+            self.stages.run(stages, state);
         }
     }
+
+    fn stage_list(&self) -> Option<&StageList> {
+        Some(&self.stages)
+    }
+
+    fn stage_list_mut(&mut self) -> Option<&mut StageList> {
+        Some(&mut self.stages)
+    }
 }
 
+/// Runs [`RenderFirst`], [`RenderPreUpdate`], [`RenderUpdate`] and [`RenderPostUpdate`] on every update.
 #[derive(Debug)]
-pub struct RenderScheduler;
+pub struct RenderScheduler {
+    stages: StageList,
+}
+
+impl Default for RenderScheduler {
+    fn default() -> Self {
+        Self {
+            stages: StageList::new()
+                .with::<RenderFirst>()
+                .with::<RenderPreUpdate>()
+                .with::<RenderUpdate>()
+                .with::<RenderPostUpdate>(),
+        }
+    }
+}
+
 impl Scheduler for RenderScheduler {
     fn schedule(&self, stages: &Stages, state: &mut State) {
         // TODO: Should have settings for min and max fps
-        let stage_ids = {
-            vec![
-                TypeId::of::<RenderFirst>(),
-                TypeId::of::<RenderPreUpdate>(),
-                TypeId::of::<RenderUpdate>(),
-                TypeId::of::<RenderPostUpdate>(),
-            ]
-        };
-
-        for &stage_id in &stage_ids {
-            stages
-                .get_by_id(&stage_id)
-                .expect("stage missing")
-                .run(state);
+        self.stages.run(stages, state);
+    }
+
+    fn stage_list(&self) -> Option<&StageList> {
+        Some(&self.stages)
+    }
+
+    fn stage_list_mut(&mut self) -> Option<&mut StageList> {
+        Some(&mut self.stages)
+    }
+}
+
+/// Runs [`Shutdown`] once, when the app shuts down.
+#[derive(Debug)]
+pub struct ShutdownScheduler {
+    stages: StageList,
+}
+
+impl Default for ShutdownScheduler {
+    fn default() -> Self {
+        Self {
+            stages: StageList::new().with::<Shutdown>(),
         }
     }
 }
 
+impl Scheduler for ShutdownScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        self.stages.run(stages, state);
+    }
+
+    fn stage_list(&self) -> Option<&StageList> {
+        Some(&self.stages)
+    }
+
+    fn stage_list_mut(&mut self) -> Option<&mut StageList> {
+        Some(&mut self.stages)
+    }
+}
+
+/// Label of the system in `First` that swaps the current and previous messages of all message types.
+///
+/// It runs after [`UPDATE_TIME`].
+pub const SWAP_MESSAGES: &str = "swap_messages";
+
+fn fixed_time<T: 'static>(state: &mut State) -> &mut FixedTime<T> {
+    state
+        .resource_mut::<FixedTime<T>>()
+        .unwrap_or_else(|| panic!("missing resource `FixedTime<{}>`", type_name::<T>()))
+}
+
 fn swap_messages(mut messages: MsgAll) {
     messages.swap_all();
 }
@@ -131,17 +239,21 @@ pub struct DefaultSchedulersPlugin;
 
 impl Plugin for DefaultSchedulersPlugin {
     fn build(&self, app: &mut App) {
-        let time = { app.resources().fetch::<MonotonicTime>().time };
+        if !app.has_resource::<FixedTime>() {
+            app.insert_resource(FixedTime::<Fixed>::default());
+        }
 
-        app.insert_resource(FixedSchedulerData {
-            consumed_up_to_time: time,
-            ticks_per_second: 60,
-        });
+        app.add_startup_scheduler(StartupScheduler::default());
 
-        app.add_scheduler(MainScheduler);
-        app.add_scheduler(FixedScheduler);
-        app.add_scheduler(RenderScheduler);
+        app.add_scheduler(MainScheduler::default());
+        app.add_scheduler(FixedScheduler::default());
+        app.add_scheduler(RenderScheduler::default());
+        app.add_shutdown_scheduler(ShutdownScheduler::default());
+
+        app.add_system(First, swap_messages.label(SWAP_MESSAGES).after(UPDATE_TIME));
+    }
 
-        app.add_system(First, swap_messages);
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![PluginDependency::plugin::<ClockPlugin>()]
     }
 }
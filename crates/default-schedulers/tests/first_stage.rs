@@ -0,0 +1,40 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_clock::{ClockPlugin, UPDATE_TIME};
+use limnus_default_schedulers::{DefaultSchedulersPlugin, SWAP_MESSAGES};
+use limnus_default_stages_plugin::DefaultStagesPlugin;
+
+#[test]
+fn messages_are_swapped_after_the_time_is_updated() {
+    let mut app = App::new();
+    app.add_plugins((DefaultStagesPlugin, ClockPlugin, DefaultSchedulersPlugin));
+
+    let plan = app.schedule_plan().unwrap();
+    let first = plan
+        .update
+        .iter()
+        .flat_map(|scheduler| &scheduler.stages)
+        .find(|stage| stage.name.ends_with("::First"))
+        .expect("First is run by the main scheduler");
+    let position = |label| {
+        first
+            .systems
+            .iter()
+            .position(|system| system.labels.contains(&label))
+            .unwrap_or_else(|| panic!("no system is labelled `{label}`"))
+    };
+    let update_time = position(UPDATE_TIME);
+    let swap_messages = position(SWAP_MESSAGES);
+
+    // Explicitly ordered, so it does not depend on the order the plugins added their systems in
+    assert!(update_time < swap_messages);
+    assert_eq!(first.systems[swap_messages].after, [UPDATE_TIME]);
+    assert!(
+        first.systems[swap_messages]
+            .runs_after
+            .contains(&update_time)
+    );
+}
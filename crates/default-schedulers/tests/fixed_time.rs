@@ -0,0 +1,215 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_clock::{Clock, ClockPlugin};
+use limnus_default_schedulers::{
+    CatchUpPolicy, DefaultSchedulersPlugin, Fixed, FixedScheduler, FixedTime,
+};
+use limnus_default_stages::FixedUpdate;
+use limnus_default_stages_plugin::DefaultStagesPlugin;
+use limnus_resource::prelude::Resource;
+use limnus_scheduler::StageList;
+use limnus_stage::StageTag;
+use limnus_system_params::{Re, ReM};
+use monotonic_time_rs::{Millis, MonotonicClock};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A clock that only moves when the test says so.
+struct ManualClock(Arc<AtomicU64>);
+
+impl MonotonicClock for ManualClock {
+    fn now(&self) -> Millis {
+        Millis::new(self.0.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+struct Ticks(Vec<u64>);
+
+fn count_ticks(time: Re<FixedTime>, mut ticks: ReM<Ticks>) {
+    ticks.0.push(time.tick());
+}
+
+/// The app and the function that moves the clock forward, in milliseconds.
+fn create_app(fixed_time: FixedTime<Fixed>) -> (App, impl Fn(u64)) {
+    create_app_with(fixed_time, |_| {})
+}
+
+fn create_app_with(
+    fixed_time: FixedTime<Fixed>,
+    setup: impl FnOnce(&mut App),
+) -> (App, impl Fn(u64)) {
+    let now = Arc::new(AtomicU64::new(1_000));
+    let mut app = App::new();
+    app.add_plugins((DefaultStagesPlugin, ClockPlugin, DefaultSchedulersPlugin));
+    app.insert_local_resource(Clock {
+        clock: Box::new(ManualClock(Arc::clone(&now))),
+    });
+    app.insert_resource(fixed_time);
+    app.insert_resource(Ticks::default());
+    app.add_system(FixedUpdate, count_ticks);
+    setup(&mut app);
+
+    // The first update only starts the accumulator
+    app.update();
+    let advance = move |millis| {
+        now.fetch_add(millis, Ordering::Relaxed);
+    };
+    (app, advance)
+}
+
+fn tick_count(app: &App) -> usize {
+    app.resource::<Ticks>().0.len()
+}
+
+#[test]
+fn steps_that_are_not_whole_milliseconds_do_not_drift() {
+    let (mut app, advance) =
+        create_app(FixedTime::from_hz(60.0).with_catch_up(CatchUpPolicy::Unlimited));
+
+    for _ in 0..100 {
+        advance(10);
+        app.update();
+    }
+
+    let time = app.resource::<FixedTime>();
+    assert_eq!(time.tick(), 60);
+    assert_eq!(tick_count(&app), 60);
+    assert_eq!(app.resource::<Ticks>().0[..3], [1, 2, 3]);
+    assert!(time.overstep() < Duration::from_millis(1));
+    assert_eq!(time.elapsed(), time.step() * 60);
+}
+
+#[test]
+fn alpha_is_how_far_real_time_is_into_the_next_step() {
+    let (mut app, advance) = create_app(FixedTime::from_step(Duration::from_millis(20)));
+
+    advance(30);
+    app.update();
+
+    let time = app.resource::<FixedTime>();
+    assert_eq!(time.tick(), 1);
+    assert_eq!(time.overstep(), Duration::from_millis(10));
+    assert!((time.alpha() - 0.5).abs() < f32::EPSILON);
+}
+
+#[test]
+fn drop_excess_limits_the_steps_and_forgets_the_rest() {
+    let (mut app, advance) = create_app(
+        FixedTime::from_step(Duration::from_millis(10))
+            .with_catch_up(CatchUpPolicy::DropExcess { max_steps: 2 }),
+    );
+
+    advance(55);
+    app.update();
+    assert_eq!(tick_count(&app), 2);
+    assert_eq!(
+        app.resource::<FixedTime>().overstep(),
+        Duration::from_millis(5)
+    );
+
+    app.update();
+    assert_eq!(tick_count(&app), 2);
+}
+
+#[test]
+fn carry_limits_the_steps_and_catches_up_later() {
+    let (mut app, advance) = create_app(
+        FixedTime::from_step(Duration::from_millis(10))
+            .with_catch_up(CatchUpPolicy::Carry { max_steps: 2 }),
+    );
+
+    advance(55);
+    app.update();
+    assert_eq!(tick_count(&app), 2);
+    assert!((app.resource::<FixedTime>().alpha() - 1.0).abs() < f32::EPSILON);
+
+    app.update();
+    app.update();
+    assert_eq!(tick_count(&app), 5);
+    assert_eq!(
+        app.resource::<FixedTime>().overstep(),
+        Duration::from_millis(5)
+    );
+}
+
+#[test]
+#[should_panic(expected = "fixed time rate must be a positive number")]
+fn rate_must_be_positive() {
+    let _ = FixedTime::<Fixed>::from_hz(0.0);
+}
+
+struct Physics;
+struct PhysicsStep;
+impl StageTag for PhysicsStep {}
+
+struct Network;
+struct NetworkSend;
+impl StageTag for NetworkSend {}
+
+#[derive(Debug, Default, Resource)]
+struct Runs(Vec<(&'static str, u64)>);
+
+fn log_gameplay(time: Re<FixedTime>, mut runs: ReM<Runs>) {
+    runs.0.push(("gameplay", time.tick()));
+}
+
+fn log_physics(time: Re<FixedTime<Physics>>, mut runs: ReM<Runs>) {
+    runs.0.push(("physics", time.tick()));
+}
+
+fn log_network(time: Re<FixedTime<Network>>, mut runs: ReM<Runs>) {
+    runs.0.push(("network", time.tick()));
+}
+
+#[test]
+fn fixed_schedulers_run_at_their_own_rates_in_order() {
+    let (mut app, advance) = create_app_with(FixedTime::from_hz(60.0), |app| {
+        app.add_stage::<PhysicsStep>();
+        app.add_stage::<NetworkSend>();
+        app.insert_resource(FixedTime::<Physics>::from_hz(120.0));
+        app.insert_resource(FixedTime::<Network>::from_hz(20.0));
+        app.add_scheduler(FixedScheduler::<Physics>::new(
+            StageList::new().with::<PhysicsStep>(),
+        ));
+        app.add_scheduler(FixedScheduler::<Network>::new(
+            StageList::new().with::<NetworkSend>(),
+        ));
+        app.insert_resource(Runs::default());
+        app.add_system(FixedUpdate, log_gameplay);
+        app.add_system(PhysicsStep, log_physics);
+        app.add_system(NetworkSend, log_network);
+    });
+
+    advance(25);
+    app.update();
+    assert_eq!(
+        app.resource::<Runs>().0,
+        [
+            ("gameplay", 1),
+            ("physics", 1),
+            ("physics", 2),
+            ("physics", 3),
+        ]
+    );
+
+    advance(25);
+    app.update();
+    assert_eq!(app.resource::<FixedTime>().tick(), 3);
+    assert_eq!(app.resource::<FixedTime<Physics>>().tick(), 6);
+    assert_eq!(app.resource::<FixedTime<Network>>().tick(), 1);
+    assert_eq!(app.resource::<Runs>().0.last(), Some(&("network", 1)));
+}
+
+#[test]
+#[should_panic(expected = "missing resource `FixedTime<fixed_time::Physics>`")]
+fn fixed_scheduler_needs_its_time() {
+    let (mut app, _advance) = create_app_with(FixedTime::from_hz(60.0), |app| {
+        app.add_scheduler(FixedScheduler::<Physics>::new(StageList::new()));
+    });
+    app.update();
+}
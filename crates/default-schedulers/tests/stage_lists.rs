@@ -0,0 +1,151 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_app::prelude::*;
+use limnus_clock::ClockPlugin;
+use limnus_default_schedulers::DefaultSchedulersPlugin;
+use limnus_default_stages::{FixedPreUpdate, FixedUpdate, PostUpdate, PreUpdate, Update};
+use limnus_default_stages_plugin::DefaultStagesPlugin;
+use limnus_resource::prelude::Resource;
+use limnus_scheduler::{ScheduleError, Scheduler, StageList};
+use limnus_stage::{StageTag, Stages};
+use limnus_system_params::ReM;
+use limnus_system_state::State;
+
+struct Physics;
+impl StageTag for Physics {}
+
+struct LateUpdate;
+impl StageTag for LateUpdate {}
+
+#[derive(Debug, Default, Resource)]
+struct Log(Vec<&'static str>);
+
+fn update(mut log: ReM<Log>) {
+    log.0.push("update");
+}
+
+fn late_update(mut log: ReM<Log>) {
+    log.0.push("late_update");
+}
+
+fn post_update(mut log: ReM<Log>) {
+    log.0.push("post_update");
+}
+
+fn create_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((DefaultStagesPlugin, ClockPlugin, DefaultSchedulersPlugin));
+    app
+}
+
+#[test]
+fn stage_is_run_where_it_was_added() {
+    let mut app = create_app();
+    app.add_stage_after::<Update, LateUpdate>();
+    app.insert_resource(Log::default());
+    app.add_system(PostUpdate, post_update);
+    app.add_system(LateUpdate, late_update);
+    app.add_system(Update, update);
+
+    app.update();
+
+    assert_eq!(
+        app.resource::<Log>().0,
+        ["update", "late_update", "post_update"]
+    );
+}
+
+#[test]
+fn stages_are_inserted_in_the_scheduler_that_runs_the_anchor() {
+    let mut app = create_app();
+    app.add_stage_before::<FixedUpdate, Physics>();
+
+    let plan = app.schedule_plan().unwrap();
+    let fixed = plan
+        .update
+        .iter()
+        .find(|scheduler| {
+            scheduler
+                .name
+                .starts_with("limnus_default_schedulers::FixedScheduler")
+        })
+        .unwrap();
+    let names: Vec<_> = fixed.stages.iter().map(|stage| stage.name).collect();
+    assert_eq!(
+        names,
+        [
+            "limnus_default_stages::FixedFirst",
+            "limnus_default_stages::FixedPreUpdate",
+            "stage_lists::Physics",
+            "limnus_default_stages::FixedUpdate",
+            "limnus_default_stages::FixedPostUpdate",
+        ]
+    );
+    assert!(app.validate_schedulers().is_ok());
+}
+
+#[test]
+#[should_panic(expected = "no scheduler runs stage `stage_lists::LateUpdate`")]
+fn anchor_must_be_run_by_a_scheduler() {
+    create_app().add_stage_after::<LateUpdate, Physics>();
+}
+
+#[derive(Debug)]
+struct ListScheduler {
+    stages: StageList,
+}
+
+impl Scheduler for ListScheduler {
+    fn schedule(&self, stages: &Stages, state: &mut State) {
+        self.stages.run(stages, state);
+    }
+
+    fn stage_list(&self) -> Option<&StageList> {
+        Some(&self.stages)
+    }
+}
+
+#[test]
+fn missing_and_duplicate_stages_are_reported() {
+    let mut app = create_app();
+    app.add_scheduler(ListScheduler {
+        stages: StageList::new().with::<Physics>(),
+    });
+    assert_eq!(
+        app.validate_schedulers(),
+        Err(ScheduleError::MissingStage {
+            scheduler: "stage_lists::ListScheduler",
+            stage: "stage_lists::Physics",
+        })
+    );
+
+    let mut app = create_app();
+    app.add_scheduler(ListScheduler {
+        stages: StageList::new().with::<PreUpdate>(),
+    });
+    let err = app.validate_schedulers().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "stage `limnus_default_stages::PreUpdate` is listed by both scheduler \
+         `limnus_default_schedulers::MainScheduler` and `stage_lists::ListScheduler`"
+    );
+}
+
+#[test]
+fn stage_list_edits() {
+    let mut list = StageList::new().with::<PreUpdate>().with::<Update>();
+    assert!(list.insert_after::<PreUpdate, Physics>());
+    assert!(list.insert_before::<PreUpdate, FixedPreUpdate>());
+    assert!(!list.insert_after::<LateUpdate, PostUpdate>());
+    assert_eq!(
+        list.names(),
+        [
+            "limnus_default_stages::FixedPreUpdate",
+            "limnus_default_stages::PreUpdate",
+            "stage_lists::Physics",
+            "limnus_default_stages::Update",
+        ]
+    );
+}
@@ -5,14 +5,19 @@
 
 use limnus_app::prelude::{App, Plugin};
 use limnus_default_stages::{
-    First, FixedFirst, FixedPostUpdate, FixedPreUpdate, FixedUpdate, PostUpdate, PreUpdate,
-    RenderFirst, RenderPostUpdate, RenderPreUpdate, RenderUpdate, Update,
+    First, FixedFirst, FixedPostUpdate, FixedPreUpdate, FixedUpdate, PostStartup, PostUpdate,
+    PreStartup, PreUpdate, RenderFirst, RenderPostUpdate, RenderPreUpdate, RenderUpdate, Shutdown,
+    Startup, Update,
 };
 
 pub struct DefaultStagesPlugin;
 
 impl Plugin for DefaultStagesPlugin {
     fn build(&self, app: &mut App) {
+        app.add_stage::<PreStartup>();
+        app.add_stage::<Startup>();
+        app.add_stage::<PostStartup>();
+
         app.add_stage::<First>();
         app.add_stage::<PreUpdate>();
         app.add_stage::<Update>();
@@ -27,5 +32,7 @@ impl Plugin for DefaultStagesPlugin {
         app.add_stage::<RenderPreUpdate>();
         app.add_stage::<RenderUpdate>();
         app.add_stage::<RenderPostUpdate>();
+
+        app.add_stage::<Shutdown>();
     }
 }
@@ -5,6 +5,18 @@
 
 use limnus_stage::StageTag;
 
+/// Runs once, when all plugins are initialized, before the first update.
+pub struct PreStartup;
+impl StageTag for PreStartup {}
+
+/// Runs once, after [`PreStartup`].
+pub struct Startup;
+impl StageTag for Startup {}
+
+/// Runs once, after [`Startup`].
+pub struct PostStartup;
+impl StageTag for PostStartup {}
+
 pub struct First;
 impl StageTag for First {}
 
@@ -40,3 +52,7 @@ impl StageTag for RenderUpdate {}
 
 pub struct RenderPostUpdate;
 impl StageTag for RenderPostUpdate {}
+
+/// Runs once, when the app shuts down, before the plugins are cleaned up and the resources are dropped.
+pub struct Shutdown;
+impl StageTag for Shutdown {}
@@ -6,45 +6,34 @@ use limnus::prelude::{
     AssetName, AssetRegistry, AssetRegistryPlugin, Assets, AudioDevicePlugin, AudioMixer,
     AudioMixerPlugin, AudioSamplePlugin, AudioStreamPlugin, Id, LocalResource, StereoSample,
 };
-use limnus_app::prelude::{App, AppReturnValue};
+use limnus_app::prelude::{App, HeadlessRunnerPlugin, HeadlessRunnerSettings};
 use limnus_assets_loader::AssetLoaderRegistryPlugin;
 use limnus_clock::ClockPlugin;
 use limnus_default_schedulers::DefaultSchedulersPlugin;
-use limnus_default_stages::Update;
+use limnus_default_stages::{Startup, Update};
 use limnus_default_stages_plugin::DefaultStagesPlugin;
 use limnus_loader::LoaderPlugin;
 use limnus_log::LogPlugin;
 use limnus_system_params::{LoReM, Re, ReM};
-use std::thread::sleep;
 use std::time::Duration;
 
-fn test_runner(mut app: App) -> AppReturnValue {
-    loop {
-        app.update();
-        sleep(Duration::from_millis(32));
-    }
-    // AppReturnValue::Value(0)
-}
-
 #[derive(Debug, LocalResource)]
 pub struct AudioTesterState {
     pub stereo_sample: Option<Id<StereoSample>>,
     pub counter: u32,
 }
 
+fn load_sample(mut asset_loader: ReM<AssetRegistry>, mut state: LoReM<AudioTesterState>) {
+    state.stereo_sample = Some(
+        asset_loader.load::<StereoSample>(AssetName::new("qubodup_whoosh").with_extension("wav")),
+    );
+}
+
 fn tick(
     mut mixer: LoReM<AudioMixer>,
     stereo_samples: Re<Assets<StereoSample>>,
-    mut asset_loader: ReM<AssetRegistry>,
     mut state: LoReM<AudioTesterState>,
 ) {
-    if state.stereo_sample.is_none() {
-        state.stereo_sample = Some(
-            asset_loader
-                .load::<StereoSample>(AssetName::new("qubodup_whoosh").with_extension("wav")),
-        );
-    }
-
     state.counter += 1;
     if state.counter % 30 == 0 {
         if let Some(found_sample_id) = &state.stereo_sample {
@@ -76,6 +65,7 @@ fn main() {
         AudioStreamPlugin,
     ));
 
+    app.add_system(Startup, load_sample);
     app.add_system(Update, tick);
 
     app.insert_local_resource(AudioTesterState {
@@ -83,7 +73,9 @@ fn main() {
         counter: 0,
     });
 
-    app.set_runner(test_runner);
+    app.add_plugins(HeadlessRunnerPlugin::new(
+        HeadlessRunnerSettings::new().with_frame_budget(Duration::from_millis(32)),
+    ));
 
     app.run();
 }
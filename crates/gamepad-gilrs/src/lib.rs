@@ -3,9 +3,9 @@
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
 use gilrs::{Error, EventType, Gilrs};
-use limnus_app::prelude::{App, Plugin};
+use limnus_app::prelude::{App, Plugin, PluginDependency};
 use limnus_default_stages::First;
-use limnus_gamepad::{Axis, Button, GamepadMessage, Gamepads};
+use limnus_gamepad::{Axis, Button, GamepadMessage, GamepadResourcePlugin, Gamepads};
 use limnus_local_resource::prelude::LocalResource;
 use limnus_message::Messages;
 use limnus_system_params::prelude::*;
@@ -136,4 +136,8 @@ impl Plugin for GamepadGilrsPlugin {
 
         app.add_system(First, check_gamepads);
     }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![PluginDependency::plugin::<GamepadResourcePlugin>()]
+    }
 }
@@ -4,7 +4,9 @@
  */
 pub mod prelude;
 
-use limnus_app::prelude::{App, AppReturnValue, Plugin};
+use limnus_app::prelude::{
+    App, AppReturnValue, Plugin, PluginCollection, PluginGroup, PluginGroupBuilder,
+};
 use limnus_asset_registry::AssetRegistryPlugin;
 use limnus_assets_loader::AssetLoaderRegistryPlugin;
 use limnus_clock::ClockPlugin;
@@ -46,32 +48,60 @@ impl Plugin for WindowRunnerPlugin {
     }
 }
 
+/// The plugins needed for a windowed game. Use [`PluginGroup::builder`] to disable, replace or add plugins.
+///
+/// Disabling a plugin also leaves out the plugins that depend on it, so disabling `AudioDevicePlugin`
+/// leaves out `AudioStreamPlugin` too. A replacement satisfies the dependencies on the plugin it replaces,
+/// so `GamepadGilrsPlugin` accepts a replacement for `GamepadResourcePlugin`.
+///
+/// ```no_run
+/// use limnus::prelude::*;
+/// use limnus::WindowRunnerPlugin;
+///
+/// App::new().add_plugins(
+///     DefaultPlugins
+///         .builder()
+///         .replace::<WindowRunnerPlugin, _>(HeadlessRunnerPlugin::default()),
+/// );
+/// ```
 pub struct DefaultPlugins;
 
-impl Plugin for DefaultPlugins {
-    fn build(&self, app: &mut App) {
-        app.add_plugins((
-            LogPlugin,
-            DefaultStagesPlugin,
-            ClockPlugin,
-            #[cfg(feature = "default_schedulers")]
-            DefaultSchedulersPlugin,
-        ));
-        app.add_plugins((LoaderPlugin, AssetLoaderRegistryPlugin, AssetRegistryPlugin));
-        app.add_plugins((WindowRunnerPlugin, WgpuWindowPlugin));
+impl PluginGroup for DefaultPlugins {
+    fn builder(self) -> PluginGroupBuilder {
+        let builder = PluginGroupBuilder::new()
+            .add(LogPlugin)
+            .add(DefaultStagesPlugin)
+            .add(ClockPlugin);
+
+        #[cfg(feature = "default_schedulers")]
+        let builder = builder.add(DefaultSchedulersPlugin);
+
+        let builder = builder
+            .add(LoaderPlugin)
+            .add(AssetLoaderRegistryPlugin)
+            .add(AssetRegistryPlugin)
+            .add(WindowRunnerPlugin)
+            .add(WgpuWindowPlugin);
 
         #[cfg(feature = "audio")]
-        app.add_plugins((
-            AudioDevicePlugin,
-            AudioSamplePlugin,
-            AudioMixerPlugin,
-            AudioStreamPlugin,
-        ));
+        let builder = builder
+            .add(AudioDevicePlugin)
+            .add(AudioSamplePlugin)
+            .add(AudioMixerPlugin)
+            .add(AudioStreamPlugin);
 
         #[cfg(feature = "gamepad")]
-        app.add_plugins((GamepadResourcePlugin, GamepadGilrsPlugin));
+        let builder = builder.add(GamepadResourcePlugin).add(GamepadGilrsPlugin);
 
         #[cfg(feature = "default_keys")]
-        app.add_plugins(DefaultKeysPlugin);
+        let builder = builder.add(DefaultKeysPlugin);
+
+        builder
+    }
+}
+
+impl PluginCollection for DefaultPlugins {
+    fn into_plugins(self) -> Vec<Box<dyn Plugin>> {
+        self.builder().into_plugins()
     }
 }
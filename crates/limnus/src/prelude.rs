@@ -4,14 +4,23 @@
  */
 #[allow(unused_imports)]
 pub use {
-    crate::DefaultPlugins, limnus_app::prelude::*, limnus_asset_id::*, limnus_asset_registry::*,
-    limnus_assets::prelude::*, limnus_assets_loader::*, limnus_basic_input::prelude::*,
-    limnus_basic_input::prelude::*, limnus_clock::prelude::*, limnus_default_stages::*,
-    limnus_local_resource::prelude::*, limnus_macros::*, limnus_message::prelude::*,
-    limnus_resource::prelude::*, limnus_scheduler::*, limnus_screen::*, limnus_system_params::*,
-    limnus_wgpu_math::*, limnus_wgpu_window::*, limnus_window::*,
+    crate::DefaultPlugins, limnus_app::prelude::*, limnus_app_state::prelude::*,
+    limnus_asset_id::*, limnus_asset_registry::*, limnus_assets::prelude::*,
+    limnus_assets_loader::*, limnus_basic_input::prelude::*, limnus_clock::prelude::*,
+    limnus_default_stages::*, limnus_local_resource::prelude::*, limnus_macros::*,
+    limnus_message::prelude::*, limnus_resource::prelude::*, limnus_scheduler::*, limnus_screen::*,
+    limnus_system_params::*, limnus_wgpu_math::*, limnus_wgpu_window::*, limnus_window::*,
 };
 
+pub use limnus_stage::{ExecutorKind, FrameDiagnostics, SystemHandle};
+pub use limnus_system::{
+    In, IntoCondition, IntoSystem, MissingParamPolicy, MissingParams, PipeFunction, SystemError,
+    SystemErrorHandler, SystemId,
+};
+
+/// For `#[derive(SystemParam)]`.
+pub use limnus_system;
+
 #[cfg(feature = "audio")]
 pub use {
     limnus_audio_device::*, limnus_audio_mixer::*, limnus_audio_sample::*, limnus_audio_stream::*,
@@ -5,6 +5,7 @@
 pub mod prelude;
 
 use std::any::{Any, TypeId, type_name};
+use std::cell::UnsafeCell;
 use std::collections::HashMap;
 use std::fmt::Debug;
 
@@ -12,9 +13,13 @@ use std::fmt::Debug;
 pub trait LocalResource: Any + Debug + 'static {}
 
 /// Storage for various resources identified by their `TypeId`.
+///
+/// Each resource is kept in its own cell, so the params of a system can borrow different
+/// resources mutably, see [`Self::get_ptr`].
 #[derive(Debug)]
 pub struct LocalResourceStorage {
     resources: HashMap<TypeId, Box<dyn Any + 'static>>,
+    insertion_order: Vec<TypeId>,
 }
 
 impl Default for LocalResourceStorage {
@@ -28,6 +33,7 @@ impl LocalResourceStorage {
     pub fn new() -> Self {
         Self {
             resources: HashMap::new(),
+            insertion_order: Vec::new(),
         }
     }
 
@@ -35,7 +41,14 @@ impl LocalResourceStorage {
     ///
     /// If a resource of the same type already exists, it will be replaced.
     pub fn insert<R: LocalResource>(&mut self, resource: R) {
-        self.resources.insert(TypeId::of::<R>(), Box::new(resource));
+        let type_id = TypeId::of::<R>();
+        if self
+            .resources
+            .insert(type_id, Box::new(UnsafeCell::new(resource)))
+            .is_none()
+        {
+            self.insertion_order.push(type_id);
+        }
     }
 
     /// Retrieves a reference to a resource of type `R`.
@@ -45,11 +58,8 @@ impl LocalResourceStorage {
     /// Panics if the resource does not exist.
     #[must_use]
     pub fn fetch<R: LocalResource>(&self) -> &R {
-        self.resources
-            .get(&TypeId::of::<R>())
+        self.get::<R>()
             .unwrap_or_else(|| panic!("LocalResource of type '{}' not found.", type_name::<R>()))
-            .downcast_ref::<R>()
-            .expect("Failed to downcast resource to the expected type.")
     }
 
     /// Retrieves a mutable reference to a resource of type `R`.
@@ -59,11 +69,8 @@ impl LocalResourceStorage {
     /// Panics if the resource does not exist.
     #[must_use]
     pub fn fetch_mut<R: LocalResource>(&mut self) -> &mut R {
-        self.resources
-            .get_mut(&TypeId::of::<R>())
+        self.get_mut::<R>()
             .unwrap_or_else(|| panic!("LocalResource of type '{}' not found.", type_name::<R>()))
-            .downcast_mut::<R>()
-            .expect("Failed to downcast resource to the expected type.")
     }
 
     /// Retrieves an immutable reference to a resource of type `R`.
@@ -71,9 +78,8 @@ impl LocalResourceStorage {
     /// Returns `Some(&R)` if the resource exists, otherwise returns `None`.
     #[must_use]
     pub fn get<R: LocalResource + 'static>(&self) -> Option<&R> {
-        self.resources
-            .get(&TypeId::of::<R>())
-            .and_then(|boxed_any| boxed_any.downcast_ref::<R>())
+        // SAFETY: Writing through the pointers from `get_ptr` requires that nothing else borrows the resource
+        self.get_ptr::<R>().map(|resource| unsafe { &*resource })
     }
 
     /// Retrieves a mutable reference to a resource of type `R`.
@@ -83,7 +89,18 @@ impl LocalResourceStorage {
     pub fn get_mut<R: LocalResource + 'static>(&mut self) -> Option<&mut R> {
         self.resources
             .get_mut(&TypeId::of::<R>())
-            .and_then(|boxed_any| boxed_any.downcast_mut::<R>())
+            .and_then(|boxed_any| boxed_any.downcast_mut::<UnsafeCell<R>>())
+            .map(UnsafeCell::get_mut)
+    }
+
+    /// A pointer to the resource, that may be written through while the storage is only borrowed
+    /// shared, as long as nothing else borrows the resource at the same time.
+    #[must_use]
+    pub fn get_ptr<R: LocalResource + 'static>(&self) -> Option<*mut R> {
+        self.resources
+            .get(&TypeId::of::<R>())
+            .and_then(|boxed_any| boxed_any.downcast_ref::<UnsafeCell<R>>())
+            .map(UnsafeCell::get)
     }
 
     /// Removes a resource of type `R` from the storage.
@@ -94,10 +111,13 @@ impl LocalResourceStorage {
     ///
     /// Panics if the resource stored is not of the expected type `R`. Should be very unlikely.
     pub fn remove<R: LocalResource>(&mut self) -> Option<R> {
-        self.resources.remove(&TypeId::of::<R>()).map(|boxed_any| {
-            *boxed_any
-                .downcast::<R>()
+        let type_id = TypeId::of::<R>();
+        self.insertion_order.retain(|id| *id != type_id);
+        self.resources.remove(&type_id).map(|boxed_any| {
+            boxed_any
+                .downcast::<UnsafeCell<R>>()
                 .expect("Failed to downcast resource to the expected type.")
+                .into_inner()
         })
     }
 
@@ -106,4 +126,13 @@ impl LocalResourceStorage {
     pub fn contains<R: LocalResource>(&self) -> bool {
         self.resources.contains_key(&TypeId::of::<R>())
     }
+
+    /// Drops all resources, the most recently inserted first.
+    ///
+    /// A resource that was replaced keeps the position of its first insertion.
+    pub fn clear(&mut self) {
+        while let Some(type_id) = self.insertion_order.pop() {
+            self.resources.remove(&type_id);
+        }
+    }
 }
@@ -6,7 +6,10 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{DeriveInput, parse_macro_input};
+use syn::visit_mut::VisitMut;
+use syn::{
+    Data, DeriveInput, Fields, GenericParam, Lifetime, Type, parse_macro_input, parse_quote,
+};
 
 #[proc_macro_derive(Resource)]
 pub fn resource_derive(input: TokenStream) -> TokenStream {
@@ -98,3 +101,96 @@ pub fn asset_derive(input: TokenStream) -> TokenStream {
 
     TokenStream::from(expanded)
 }
+
+/// Replaces all lifetimes with `'static`, since params are only implemented for their `'static` variant.
+struct StaticLifetimes;
+
+impl VisitMut for StaticLifetimes {
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        *lifetime = parse_quote!('static);
+    }
+}
+
+/// Implements `SystemParam` for a struct where every field is a system param, including other structs
+/// with this derive. The struct is fetched if all of its fields can be fetched.
+///
+/// The struct can have at most one lifetime, which the params borrow from the `State` with.
+/// Expects the `limnus_system` crate to be reachable by that name.
+#[proc_macro_derive(SystemParam)]
+pub fn system_param_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return syn::Error::new_spanned(name, "SystemParam can only be derived for structs")
+            .to_compile_error()
+            .into();
+    };
+
+    let mut generics = input.generics.clone();
+    generics.params = generics
+        .params
+        .into_iter()
+        .filter(|param| !matches!(param, GenericParam::Lifetime(_)))
+        .collect();
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let type_arguments = input.generics.params.iter().map(|param| match param {
+        GenericParam::Lifetime(_) => quote! { 'static },
+        GenericParam::Type(type_param) => {
+            let ident = &type_param.ident;
+            quote! { #ident }
+        }
+        GenericParam::Const(const_param) => {
+            let ident = &const_param.ident;
+            quote! { #ident }
+        }
+    });
+
+    let field_types: Vec<Type> = data
+        .fields
+        .iter()
+        .map(|field| {
+            let mut field_type = field.ty.clone();
+            StaticLifetimes.visit_type_mut(&mut field_type);
+            field_type
+        })
+        .collect();
+
+    // The locals of the fields are nested pairs, `(first, (second, ()))`, like the ones of param tuples
+    let local_type = field_types
+        .iter()
+        .rev()
+        .fold(quote! { () }, |rest, field_type| {
+            quote! { (<#field_type as limnus_system::SystemParam>::Local, #rest) }
+        });
+    let fetch = field_types.iter().enumerate().map(|(index, field_type)| {
+        let rest = (0..index).map(|_| quote! { .1 });
+        quote! { unsafe { <#field_type as limnus_system::SystemParam>::get(state, &mut local #(#rest)* .0) }? }
+    });
+    let construct = match &data.fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote! { Self { #(#names: #fetch),* } }
+        }
+        Fields::Unnamed(_) => quote! { Self(#(#fetch),*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    let expanded = quote! {
+        impl #impl_generics limnus_system::SystemParam for #name<#(#type_arguments),*> #where_clause {
+            type Item = Self;
+            type Local = #local_type;
+
+            #[allow(unused_variables)]
+            unsafe fn get(state: limnus_system::StateCell<'_>, local: &mut Self::Local) -> Option<Self::Item> {
+                Some(#construct)
+            }
+
+            fn access(access: &mut limnus_system::Access) {
+                #(<#field_types as limnus_system::SystemParam>::access(access);)*
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
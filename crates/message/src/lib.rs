@@ -6,6 +6,7 @@ pub mod prelude;
 
 use std::{
     any::{Any, TypeId},
+    cell::UnsafeCell,
     collections::HashMap,
     fmt::Debug,
     marker::PhantomData,
@@ -204,7 +205,8 @@ impl<M: Message> DoubleEndedIterator for MessagesIterator<'_, M> {
 /// Trait for type-erased message containers.
 ///
 /// This allows for storing heterogeneous `Messages<M>` containers within a single collection.
-pub trait MessageContainer: Any + Send + Sync {
+/// Each container is kept in its own cell, see [`MessageStorage::get_ptr`].
+pub trait MessageContainer: Any + Send {
     /// Swaps the current and previous frame message lists.
     fn swap(&mut self);
 
@@ -215,9 +217,9 @@ pub trait MessageContainer: Any + Send + Sync {
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
-impl<M: Message> MessageContainer for Messages<M> {
+impl<M: Message> MessageContainer for UnsafeCell<Messages<M>> {
     fn swap(&mut self) {
-        self.swap();
+        self.get_mut().swap();
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -274,7 +276,7 @@ impl MessageStorage {
         let type_id = TypeId::of::<M>();
         self.registry.insert(
             type_id,
-            Box::new(Messages::<M>::new()) as Box<dyn MessageContainer>,
+            Box::new(UnsafeCell::new(Messages::<M>::new())) as Box<dyn MessageContainer>,
         );
     }
 
@@ -304,7 +306,8 @@ impl MessageStorage {
     pub fn get_mut<M: Message>(&mut self) -> Option<&mut Messages<M>> {
         self.registry
             .get_mut(&TypeId::of::<M>())
-            .and_then(|boxed| boxed.as_any_mut().downcast_mut::<Messages<M>>())
+            .and_then(|boxed| boxed.as_any_mut().downcast_mut::<UnsafeCell<Messages<M>>>())
+            .map(UnsafeCell::get_mut)
     }
 
     /// Retrieves an immutable reference to the `Messages<M>` container for the specified message type.
@@ -331,9 +334,20 @@ impl MessageStorage {
     /// ```
     #[must_use]
     pub fn get<M: Message>(&self) -> Option<&Messages<M>> {
+        // SAFETY: Writing through the pointers from `get_ptr` requires that nothing else borrows the messages
+        self.get_ptr::<M>().map(|messages| unsafe { &*messages })
+    }
+
+    /// A pointer to the `Messages<M>` container, that may be written through while the storage is
+    /// only borrowed shared, as long as nothing else borrows the container at the same time.
+    ///
+    /// Returns `None` if the message type has not been registered.
+    #[must_use]
+    pub fn get_ptr<M: Message>(&self) -> Option<*mut Messages<M>> {
         self.registry
             .get(&TypeId::of::<M>())
-            .and_then(|boxed| boxed.as_any().downcast_ref::<Messages<M>>())
+            .and_then(|boxed| boxed.as_any().downcast_ref::<UnsafeCell<Messages<M>>>())
+            .map(UnsafeCell::get)
     }
 
     /// Swaps the current and previous frame message lists for all registered message types.
@@ -2,13 +2,17 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
-use limnus_scheduler::Scheduler;
-use limnus_stage::Stages;
+use limnus_scheduler::{ScheduleError, Scheduler, StageList, validate_stage_lists};
+use limnus_stage::{FrameDiagnostics, StageId, Stages};
 use limnus_system_state::State;
+use std::time::Instant;
+use tracing::debug_span;
 
 #[derive(Default)]
 pub struct Runner {
     schedulers: Vec<Box<dyn Scheduler>>,
+    startup_schedulers: Vec<Box<dyn Scheduler>>,
+    shutdown_schedulers: Vec<Box<dyn Scheduler>>,
 }
 
 impl Runner {
@@ -16,6 +20,8 @@ impl Runner {
     pub fn new() -> Self {
         Self {
             schedulers: Vec::new(),
+            startup_schedulers: Vec::new(),
+            shutdown_schedulers: Vec::new(),
         }
     }
 
@@ -26,9 +32,108 @@ impl Runner {
         self.schedulers.push(Box::new(schedule));
     }
 
+    /// The schedulers that run on each update, in the order they run.
+    #[must_use]
+    pub fn schedulers(&self) -> &[Box<dyn Scheduler>] {
+        &self.schedulers
+    }
+
+    #[must_use]
+    pub fn startup_schedulers(&self) -> &[Box<dyn Scheduler>] {
+        &self.startup_schedulers
+    }
+
+    #[must_use]
+    pub fn shutdown_schedulers(&self) -> &[Box<dyn Scheduler>] {
+        &self.shutdown_schedulers
+    }
+
+    /// The editable stage list that contains the stage, from the startup, update or shutdown schedulers.
+    pub fn stage_list_containing_mut(&mut self, stage_id: &StageId) -> Option<&mut StageList> {
+        self.startup_schedulers
+            .iter_mut()
+            .chain(&mut self.schedulers)
+            .chain(&mut self.shutdown_schedulers)
+            .filter_map(|scheduler| scheduler.stage_list_mut())
+            .find(|list| list.contains(stage_id))
+    }
+
+    /// Checks the stage lists of all schedulers, see [`validate_stage_lists`].
+    ///
+    /// # Errors
+    /// If a stage is missing or listed more than once.
+    pub fn validate(&self, stages: &Stages) -> Result<(), ScheduleError> {
+        let lists = self
+            .startup_schedulers
+            .iter()
+            .chain(&self.schedulers)
+            .chain(&self.shutdown_schedulers)
+            .filter_map(|scheduler| Some((scheduler.name(), scheduler.stage_list()?)));
+        validate_stage_lists(lists, stages)
+    }
+
     pub fn run_schedulers(&self, stages: &Stages, state: &mut State) {
-        for scheduler in &self.schedulers {
-            scheduler.schedule(stages, state);
+        run(&self.schedulers, stages, state);
+    }
+
+    /// Adds a scheduler that runs before all schedulers that have already been added.
+    pub fn add_scheduler_first<T>(&mut self, schedule: T)
+    where
+        T: Scheduler,
+    {
+        self.schedulers.insert(0, Box::new(schedule));
+    }
+
+    /// Adds a scheduler that runs right after the last already added scheduler that matches `after`,
+    /// or before all of them if none matches.
+    pub fn add_scheduler_after_last<T>(
+        &mut self,
+        after: impl Fn(&dyn Scheduler) -> bool,
+        schedule: T,
+    ) where
+        T: Scheduler,
+    {
+        let index = self
+            .schedulers
+            .iter()
+            .rposition(|scheduler| after(scheduler.as_ref()))
+            .map_or(0, |index| index + 1);
+        self.schedulers.insert(index, Box::new(schedule));
+    }
+
+    /// Adds a scheduler that is only run once, before the first update of the running app.
+    pub fn add_startup_scheduler<T>(&mut self, schedule: T)
+    where
+        T: Scheduler,
+    {
+        self.startup_schedulers.push(Box::new(schedule));
+    }
+
+    pub fn run_startup_schedulers(&self, stages: &Stages, state: &mut State) {
+        run(&self.startup_schedulers, stages, state);
+    }
+
+    /// Adds a scheduler that is only run once, when the app shuts down.
+    pub fn add_shutdown_scheduler<T>(&mut self, schedule: T)
+    where
+        T: Scheduler,
+    {
+        self.shutdown_schedulers.push(Box::new(schedule));
+    }
+
+    pub fn run_shutdown_schedulers(&self, stages: &Stages, state: &mut State) {
+        run(&self.shutdown_schedulers, stages, state);
+    }
+}
+
+/// Runs each scheduler in a `tracing` span, and times it if the [`FrameDiagnostics`] resource exists.
+fn run(schedulers: &[Box<dyn Scheduler>], stages: &Stages, state: &mut State) {
+    for scheduler in schedulers {
+        let _span = debug_span!("scheduler", name = scheduler.name()).entered();
+        let start = Instant::now();
+        scheduler.schedule(stages, state);
+        if let Some(diagnostics) = state.resource_mut::<FrameDiagnostics>() {
+            diagnostics.record_scheduler(scheduler.name(), start.elapsed());
         }
     }
 }
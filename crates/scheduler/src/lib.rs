@@ -2,11 +2,37 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
+mod stage_list;
 
-use limnus_stage::Stages;
+pub use stage_list::{ScheduleError, StageList, validate_stage_lists};
+
+use limnus_stage::{StageId, Stages};
 use limnus_system_state::State;
+use std::any::type_name;
 use std::fmt::Debug;
 
 pub trait Scheduler: Debug + 'static {
     fn schedule(&self, stages: &Stages, state: &mut State);
+
+    /// Used in diagnostics, the type name of the scheduler by default.
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+
+    /// The stages that the scheduler runs, in order, for schedule exports. Empty if it is not known up front.
+    ///
+    /// The ids in the [`Self::stage_list`] by default.
+    fn stage_ids(&self) -> Vec<StageId> {
+        self.stage_list().map(StageList::ids).unwrap_or_default()
+    }
+
+    /// Schedulers that run an editable list of stages return it here, so that other stages
+    /// can be added next to them and the list can be validated at startup.
+    fn stage_list(&self) -> Option<&StageList> {
+        None
+    }
+
+    fn stage_list_mut(&mut self) -> Option<&mut StageList> {
+        None
+    }
 }
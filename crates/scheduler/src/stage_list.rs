@@ -0,0 +1,164 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_stage::{StageId, StageTag, Stages};
+use limnus_system_state::State;
+use std::any::type_name;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The stages that a scheduler runs, in order.
+///
+/// Plugins can add their own stages next to the existing ones, see `App::add_stage_after`.
+#[derive(Debug, Clone, Default)]
+pub struct StageList {
+    stages: Vec<(StageId, &'static str)>,
+}
+
+impl StageList {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Adds the stage last.
+    #[must_use]
+    pub fn with<S: StageTag>(mut self) -> Self {
+        self.push::<S>();
+        self
+    }
+
+    /// Adds the stage last.
+    pub fn push<S: StageTag>(&mut self) {
+        self.stages.push((StageId::of::<S>(), type_name::<S>()));
+    }
+
+    /// Adds the stage `S` right after the stage `After`.
+    ///
+    /// Returns `false`, without adding anything, if `After` is not in the list.
+    pub fn insert_after<After: StageTag, S: StageTag>(&mut self) -> bool {
+        self.insert_at::<S>(
+            self.position_of(&StageId::of::<After>())
+                .map(|index| index + 1),
+        )
+    }
+
+    /// Adds the stage `S` right before the stage `Before`.
+    ///
+    /// Returns `false`, without adding anything, if `Before` is not in the list.
+    pub fn insert_before<Before: StageTag, S: StageTag>(&mut self) -> bool {
+        self.insert_at::<S>(self.position_of(&StageId::of::<Before>()))
+    }
+
+    fn insert_at<S: StageTag>(&mut self, index: Option<usize>) -> bool {
+        let Some(index) = index else {
+            return false;
+        };
+        self.stages
+            .insert(index, (StageId::of::<S>(), type_name::<S>()));
+        true
+    }
+
+    fn position_of(&self, stage_id: &StageId) -> Option<usize> {
+        self.stages.iter().position(|(id, _)| id == stage_id)
+    }
+
+    #[must_use]
+    pub fn contains(&self, stage_id: &StageId) -> bool {
+        self.position_of(stage_id).is_some()
+    }
+
+    /// The ids of the stages, in the order they run.
+    #[must_use]
+    pub fn ids(&self) -> Vec<StageId> {
+        self.stages.iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    /// The type names of the stage tags, in the order they run.
+    #[must_use]
+    pub fn names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|(_, name)| *name).collect()
+    }
+
+    /// Runs the stages in order.
+    ///
+    /// # Panics
+    /// If a stage is missing, which [`validate_stage_lists`] reports up front.
+    pub fn run(&self, stages: &Stages, state: &mut State) {
+        for (stage_id, name) in &self.stages {
+            stages
+                .get_by_stage_id(stage_id)
+                .unwrap_or_else(|| panic!("could not find stage `{name}`"))
+                .run(state);
+        }
+    }
+}
+
+/// A problem with the stage lists of the schedulers, see [`validate_stage_lists`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ScheduleError {
+    /// The scheduler lists a stage that has not been added.
+    MissingStage {
+        scheduler: &'static str,
+        stage: &'static str,
+    },
+    /// The stage is listed more than once, so it would run more than once in a frame.
+    DuplicateStage {
+        stage: &'static str,
+        first: &'static str,
+        second: &'static str,
+    },
+}
+
+impl Display for ScheduleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingStage { scheduler, stage } => {
+                write!(
+                    f,
+                    "scheduler `{scheduler}` lists stage `{stage}`, which has not been added"
+                )
+            }
+            Self::DuplicateStage {
+                stage,
+                first,
+                second,
+            } => write!(
+                f,
+                "stage `{stage}` is listed by both scheduler `{first}` and `{second}`"
+            ),
+        }
+    }
+}
+
+impl Error for ScheduleError {}
+
+/// Checks that every stage in the lists exists, and that no stage is listed twice.
+///
+/// `lists` holds the name of each scheduler together with its stage list.
+///
+/// # Errors
+/// The first [`ScheduleError`] that is encountered.
+pub fn validate_stage_lists<'a>(
+    lists: impl IntoIterator<Item = (&'static str, &'a StageList)>,
+    stages: &Stages,
+) -> Result<(), ScheduleError> {
+    let mut seen: Vec<(StageId, &'static str)> = Vec::new();
+    for (scheduler, list) in lists {
+        for (stage_id, stage) in &list.stages {
+            if stages.get_by_stage_id(stage_id).is_none() {
+                return Err(ScheduleError::MissingStage { scheduler, stage });
+            }
+            if let Some((_, first)) = seen.iter().find(|(id, _)| id == stage_id) {
+                return Err(ScheduleError::DuplicateStage {
+                    stage,
+                    first,
+                    second: scheduler,
+                });
+            }
+            seen.push((stage_id.clone(), scheduler));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,233 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::StageId;
+use limnus_resource::Resource;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// The durations of the latest runs of a system, stage or scheduler.
+#[derive(Debug, Clone)]
+pub struct Timings {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl Timings {
+    fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, duration: Duration) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(duration);
+    }
+
+    #[must_use]
+    pub fn last(&self) -> Option<Duration> {
+        self.samples.back().copied()
+    }
+
+    /// Zero if there are no samples.
+    #[must_use]
+    pub fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    /// Zero if there are no samples.
+    #[must_use]
+    pub fn max(&self) -> Duration {
+        self.samples.iter().max().copied().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// From the oldest to the latest sample.
+    pub fn samples(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.samples.iter().copied()
+    }
+}
+
+/// Resource that collects how long the latest runs of each system, stage and scheduler took.
+///
+/// Nothing is measured unless this resource has been inserted.
+/// Stages are identified by their [`StageId`], since stages with a tag value, like `OnEnter(GameState::Menu)`,
+/// share the name of the tag type. Systems are identified by the stage id together with the system name.
+#[derive(Debug)]
+pub struct FrameDiagnostics {
+    capacity: usize,
+    /// The stage name is kept next to the timings, for reports.
+    systems: HashMap<(StageId, &'static str), (&'static str, Timings)>,
+    stages: HashMap<StageId, (&'static str, Timings)>,
+    schedulers: HashMap<&'static str, Timings>,
+}
+
+impl Resource for FrameDiagnostics {}
+
+impl Default for FrameDiagnostics {
+    /// Keeps the 60 latest runs.
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+impl FrameDiagnostics {
+    /// Keeps the `capacity` latest runs of each system, stage and scheduler.
+    ///
+    /// # Panics
+    /// If `capacity` is zero.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "frame diagnostics must keep at least one run");
+        Self {
+            capacity,
+            systems: HashMap::new(),
+            stages: HashMap::new(),
+            schedulers: HashMap::new(),
+        }
+    }
+
+    pub fn record_system(
+        &mut self,
+        stage_id: &StageId,
+        stage: &'static str,
+        system: &'static str,
+        duration: Duration,
+    ) {
+        let capacity = self.capacity;
+        self.systems
+            .entry((stage_id.clone(), system))
+            .or_insert_with(|| (stage, Timings::new(capacity)))
+            .1
+            .push(duration);
+    }
+
+    pub fn record_stage(&mut self, stage_id: &StageId, stage: &'static str, duration: Duration) {
+        let capacity = self.capacity;
+        self.stages
+            .entry(stage_id.clone())
+            .or_insert_with(|| (stage, Timings::new(capacity)))
+            .1
+            .push(duration);
+    }
+
+    pub fn record_scheduler(&mut self, scheduler: &'static str, duration: Duration) {
+        let capacity = self.capacity;
+        self.schedulers
+            .entry(scheduler)
+            .or_insert_with(|| Timings::new(capacity))
+            .push(duration);
+    }
+
+    #[must_use]
+    pub fn system(&self, stage_id: &StageId, system: &str) -> Option<&Timings> {
+        self.systems
+            .iter()
+            .find(|((system_stage_id, name), _)| system_stage_id == stage_id && *name == system)
+            .map(|(_, (_, timings))| timings)
+    }
+
+    #[must_use]
+    pub fn stage(&self, stage_id: &StageId) -> Option<&Timings> {
+        self.stages.get(stage_id).map(|(_, timings)| timings)
+    }
+
+    #[must_use]
+    pub fn scheduler(&self, scheduler: &str) -> Option<&Timings> {
+        self.schedulers.get(scheduler)
+    }
+
+    /// The stage name, system name and timings of every system, slowest on average first.
+    #[must_use]
+    pub fn systems(&self) -> Vec<(&'static str, &'static str, &Timings)> {
+        let mut systems: Vec<_> = self
+            .systems
+            .iter()
+            .map(|(&(_, system), (stage, timings))| (*stage, system, timings))
+            .collect();
+        systems.sort_by(|a, b| b.2.average().cmp(&a.2.average()).then(a.1.cmp(b.1)));
+        systems
+    }
+
+    /// The stage name and timings of every stage, slowest on average first.
+    ///
+    /// Stages with a tag value have the same name as the other stages of that tag type.
+    #[must_use]
+    pub fn stages(&self) -> Vec<(&'static str, &Timings)> {
+        sorted_by_average(self.stages.values().map(|(name, timings)| (*name, timings)))
+    }
+
+    /// Slowest on average first.
+    #[must_use]
+    pub fn schedulers(&self) -> Vec<(&'static str, &Timings)> {
+        sorted_by_average(
+            self.schedulers
+                .iter()
+                .map(|(&name, timings)| (name, timings)),
+        )
+    }
+
+    /// Forgets all samples.
+    pub fn clear(&mut self) {
+        self.systems.clear();
+        self.stages.clear();
+        self.schedulers.clear();
+    }
+}
+
+fn sorted_by_average<'a>(
+    timings: impl Iterator<Item = (&'static str, &'a Timings)>,
+) -> Vec<(&'static str, &'a Timings)> {
+    let mut sorted: Vec<_> = timings.collect();
+    sorted.sort_by(|a, b| b.1.average().cmp(&a.1.average()).then(a.0.cmp(b.0)));
+    sorted
+}
+
+/// One line for each scheduler, stage and system, with the average and max durations.
+impl Display for FrameDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (name, timings) in self.schedulers() {
+            writeln!(
+                f,
+                "scheduler {name}: avg {:?} max {:?}",
+                timings.average(),
+                timings.max()
+            )?;
+        }
+        for (name, timings) in self.stages() {
+            writeln!(
+                f,
+                "stage {name}: avg {:?} max {:?}",
+                timings.average(),
+                timings.max()
+            )?;
+        }
+        for (stage, name, timings) in self.systems() {
+            writeln!(
+                f,
+                "system {name} in {stage}: avg {:?} max {:?}",
+                timings.average(),
+                timings.max()
+            )?;
+        }
+        Ok(())
+    }
+}
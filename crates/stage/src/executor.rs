@@ -0,0 +1,298 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_system::{Access, BoxedError, SystemConfig};
+use limnus_system_state::{State, StateCell};
+use std::any::Any;
+use std::num::NonZeroUsize;
+use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{Span, debug_span};
+
+/// How the systems in a stage are run, see [`crate::Stage::set_executor`].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum ExecutorKind {
+    /// One system at a time, on the calling thread.
+    #[default]
+    Serial,
+    /// Systems with compatible [`Access`] run at the same time on a thread pool.
+    /// Systems that use local resources always run on the calling thread.
+    ///
+    /// `before` and `after` constraints are kept, but systems that conflict without being ordered
+    /// against each other can run in any order.
+    Parallel,
+    /// Like [`Self::Parallel`], but systems that conflict always run in the same order as with
+    /// [`Self::Serial`], so the result is the same as running them one at a time.
+    DeterministicParallel,
+}
+
+/// The dependencies between the systems in a stage, for the parallel executors.
+pub(crate) struct ParallelSchedule {
+    /// The position of each system in the serial order, used to prioritize ready systems.
+    position: Vec<usize>,
+    predecessor_count: Vec<usize>,
+    successors: Vec<Vec<usize>>,
+}
+
+impl ParallelSchedule {
+    pub fn new(
+        order: &[usize],
+        predecessors: &[Vec<usize>],
+        accesses: &[Access],
+        deterministic: bool,
+    ) -> Self {
+        let count = order.len();
+        let mut all_predecessors = predecessors.to_vec();
+
+        if deterministic {
+            for (later_position, &later) in order.iter().enumerate() {
+                for &earlier in &order[..later_position] {
+                    if !accesses[earlier].is_compatible(&accesses[later]) {
+                        all_predecessors[later].push(earlier);
+                    }
+                }
+            }
+        }
+
+        let mut position = vec![0; count];
+        for (index, &system) in order.iter().enumerate() {
+            position[system] = index;
+        }
+
+        let mut predecessor_count = vec![0; count];
+        let mut successors = vec![Vec::new(); count];
+        for (system, predecessors) in all_predecessors.iter_mut().enumerate() {
+            predecessors.sort_unstable();
+            predecessors.dedup();
+            predecessor_count[system] = predecessors.len();
+            for &predecessor in predecessors.iter() {
+                successors[predecessor].push(system);
+            }
+        }
+
+        Self {
+            position,
+            predecessor_count,
+            successors,
+        }
+    }
+}
+
+type Completion = (usize, thread::Result<Result<(), BoxedError>>, Duration);
+
+struct Job {
+    index: usize,
+    config: *const SystemConfig,
+    state: StateCell<'static>,
+    span: Span,
+    completed: Sender<Completion>,
+}
+
+// SAFETY: The executor waits for every job to complete before returning, so the config and the state
+// outlive the job. The config is `Sync`, since `SystemConfig` only holds `Send + Sync` systems.
+unsafe impl Send for Job {}
+
+impl Job {
+    fn run(self) {
+        let start = Instant::now();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _entered = self.span.enter();
+            // SAFETY: See `run_parallel`
+            unsafe { (*self.config).system().run_shared(self.state) }
+        }));
+        // The executor keeps the receiver until all jobs are completed
+        let _ = self.completed.send((self.index, result, start.elapsed()));
+    }
+}
+
+struct WorkerPool {
+    jobs: Mutex<Sender<Job>>,
+}
+
+impl WorkerPool {
+    fn new() -> Self {
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let worker_count = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        for worker_index in 0..worker_count {
+            let receiver: Arc<Mutex<Receiver<Job>>> = Arc::clone(&receiver);
+            thread::Builder::new()
+                .name(format!("limnus-worker-{worker_index}"))
+                .spawn(move || {
+                    loop {
+                        let job = receiver.lock().expect("worker pool receiver").recv();
+                        match job {
+                            Ok(job) => job.run(),
+                            Err(_) => break,
+                        }
+                    }
+                })
+                .expect("could not spawn worker thread");
+        }
+
+        Self {
+            jobs: Mutex::new(sender),
+        }
+    }
+
+    fn spawn(&self, job: Job) {
+        self.jobs
+            .lock()
+            .expect("worker pool sender")
+            .send(job)
+            .expect("worker pool has stopped");
+    }
+}
+
+fn worker_pool() -> &'static WorkerPool {
+    static POOL: OnceLock<WorkerPool> = OnceLock::new();
+    POOL.get_or_init(WorkerPool::new)
+}
+
+/// Runs the systems on the worker pool, while the calling thread coordinates and runs the systems
+/// that must be on the main thread, and some of the others when it is free.
+///
+/// If a system panics, no more systems are started, and the panic is resumed when the running systems are done.
+///
+/// Disabled systems are completed without running, as soon as their predecessors are done.
+///
+/// Returns the errors from the systems that failed, in serial order, since other systems could be running
+/// when they fail, and how long each system took.
+pub(crate) fn run_parallel(
+    systems: &[SystemConfig],
+    accesses: &[Access],
+    enabled: &[bool],
+    schedule: &ParallelSchedule,
+    state: &mut State,
+) -> ParallelRun {
+    let count = systems.len();
+    // SAFETY: Every job is completed before this function returns, also after a panic.
+    // The systems only reach the state through the cell, and systems whose access conflicts are never
+    // running at the same time, so no two systems borrow the same data mutably.
+    // Systems that touch the local resources, which are not `Send`, only run on the calling thread.
+    let state = unsafe { StateCell::new(state).into_static() };
+    let (completed_sender, completed_receiver) = channel::<Completion>();
+
+    let mut waiting_for = schedule.predecessor_count.clone();
+    let mut ready: Vec<usize> = (0..count)
+        .filter(|&index| waiting_for[index] == 0)
+        .collect();
+    ready.sort_unstable_by_key(|&index| schedule.position[index]);
+    let mut running: Vec<usize> = Vec::new();
+    let mut completed_count = 0;
+    let mut panic_payload: Option<Box<dyn Any + Send>> = None;
+    let mut errors = Vec::new();
+    let mut durations = vec![Duration::ZERO; count];
+
+    while completed_count < count {
+        let mut inline_system = None;
+        let mut started = Vec::new();
+        if panic_payload.is_none() {
+            ready.retain(|&index| {
+                if !enabled[index] {
+                    // Completed by the calling thread, without any access
+                    if inline_system.is_some() {
+                        return true;
+                    }
+                    inline_system = Some(index);
+                    return false;
+                }
+                let access = &accesses[index];
+                // A disabled inline system is not run, so it cannot conflict
+                let inline_running = inline_system.filter(|&inline| enabled[inline]);
+                if !running
+                    .iter()
+                    .chain(&started)
+                    .chain(inline_running.as_ref())
+                    .all(|&other| accesses[other].is_compatible(access))
+                {
+                    return true;
+                }
+                if access.requires_main_thread() {
+                    if inline_system.is_some() {
+                        return true;
+                    }
+                    inline_system = Some(index);
+                } else {
+                    started.push(index);
+                }
+                false
+            });
+        }
+
+        // The calling thread would otherwise only wait, so it runs one of the systems itself
+        if inline_system.is_none() {
+            inline_system = started.pop();
+        }
+        for &index in &started {
+            worker_pool().spawn(Job {
+                index,
+                config: &raw const systems[index],
+                state,
+                span: debug_span!("system", name = systems[index].system().name()),
+                completed: completed_sender.clone(),
+            });
+        }
+        running.extend(started);
+
+        let (index, result, duration) = if let Some(index) = inline_system {
+            if enabled[index] {
+                let start = Instant::now();
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    let _span =
+                        debug_span!("system", name = systems[index].system().name()).entered();
+                    // SAFETY: See above
+                    unsafe { systems[index].system().run_shared(state) }
+                }));
+                (index, result, start.elapsed())
+            } else {
+                (index, Ok(Ok(())), Duration::ZERO)
+            }
+        } else if running.is_empty() {
+            // Only happens after a panic, when there is nothing left to wait for
+            break;
+        } else {
+            completed_receiver
+                .recv()
+                .expect("the executor holds a sender")
+        };
+
+        running.retain(|&other| other != index);
+        completed_count += 1;
+        durations[index] = duration;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => errors.push((index, error)),
+            Err(payload) => {
+                panic_payload.get_or_insert(payload);
+            }
+        }
+
+        for &successor in &schedule.successors[index] {
+            waiting_for[successor] -= 1;
+            if waiting_for[successor] == 0 {
+                let position = schedule.position[successor];
+                let insert_at = ready.partition_point(|&other| schedule.position[other] < position);
+                ready.insert(insert_at, successor);
+            }
+        }
+    }
+
+    if let Some(payload) = panic_payload {
+        resume_unwind(payload);
+    }
+
+    errors.sort_by_key(|(index, _)| schedule.position[*index]);
+    ParallelRun { errors, durations }
+}
+
+/// The outcome of [`run_parallel`].
+pub(crate) struct ParallelRun {
+    pub errors: Vec<(usize, BoxedError)>,
+    /// For each system, zero for the ones that were not run or are disabled.
+    pub durations: Vec<Duration>,
+}
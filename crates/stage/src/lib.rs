@@ -2,23 +2,179 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
-use limnus_system::{IntoSystem, System, SystemParam};
+mod diagnostics;
+mod executor;
+mod order;
+
+pub use diagnostics::{FrameDiagnostics, Timings};
+pub use executor::ExecutorKind;
+pub use order::StageError;
+
+use crate::executor::{ParallelSchedule, run_parallel};
+use crate::order::sorted_order;
+use limnus_system::{
+    Access, BoxedError, IntoSystemConfig, SystemConfig, SystemError, handle_missing_param,
+    handle_system_error,
+};
 use limnus_system_state::State;
-use std::any::TypeId;
+use std::any::{Any, TypeId, type_name};
+use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::debug_span;
 
 /// A marker trait used to uniquely identify stages.
 ///
 /// Implement this trait for any type you wish to use as a stage identifier.
-pub trait StageTag: 'static {}
+/// Tags that carry a value, like `OnEnter(GameState::Menu)`, override [`StageTag::stage_id`]
+/// so that each value gets its own stage.
+pub trait StageTag: 'static {
+    /// Stages for this tag are created by `App::add_system` when they are missing,
+    /// instead of having to be added up front.
+    const CREATED_ON_DEMAND: bool = false;
+
+    fn stage_id(&self) -> StageId
+    where
+        Self: Sized,
+    {
+        StageId::of::<Self>()
+    }
+}
+
+/// The tag value of a [`StageId`], compared and hashed through the concrete type.
+trait StageValue: Any + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_value(&self, other: &dyn StageValue) -> bool;
+    fn hash_value(&self, state: &mut dyn Hasher);
+}
+
+impl<V: Any + Eq + Hash + Send + Sync> StageValue for V {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_value(&self, other: &dyn StageValue) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<V>()
+            .is_some_and(|other| self == other)
+    }
+
+    fn hash_value(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+}
+
+/// Identifies a stage by the type of its tag and, optionally, the tag value.
+#[derive(Clone)]
+pub struct StageId {
+    type_id: TypeId,
+    value: Option<Arc<dyn StageValue>>,
+}
+
+impl StageId {
+    #[must_use]
+    pub fn of<S: StageTag>() -> Self {
+        Self::from_type_id(TypeId::of::<S>())
+    }
+
+    #[must_use]
+    pub const fn from_type_id(type_id: TypeId) -> Self {
+        Self {
+            type_id,
+            value: None,
+        }
+    }
+
+    /// A stage id for the tag type `S` that is unique for each `value`.
+    ///
+    /// The value is kept in the id, so two values are only the same stage if they are equal.
+    #[must_use]
+    pub fn with_value<S: StageTag, V: Eq + Hash + Send + Sync + 'static>(value: V) -> Self {
+        Self {
+            type_id: TypeId::of::<S>(),
+            value: Some(Arc::new(value)),
+        }
+    }
+
+    #[must_use]
+    pub const fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+}
+
+impl PartialEq for StageId {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id == other.type_id
+            && match (&self.value, &other.value) {
+                (None, None) => true,
+                (Some(value), Some(other_value)) => value.eq_value(other_value.as_ref()),
+                _ => false,
+            }
+    }
+}
+
+impl Eq for StageId {}
+
+impl Hash for StageId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.type_id.hash(state);
+        if let Some(value) = &self.value {
+            value.hash_value(state);
+        }
+    }
+}
+
+impl Debug for StageId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StageId")
+            .field("type_id", &self.type_id)
+            .field("has_value", &self.value.is_some())
+            .finish()
+    }
+}
+
+impl From<TypeId> for StageId {
+    fn from(type_id: TypeId) -> Self {
+        Self::from_type_id(type_id)
+    }
+}
+
+/// Identifies a system within its [`Stage`], see [`Stage::add_system`].
+///
+/// Keys are unique across all stages, so they can be created before the system is added,
+/// see [`Stage::insert_system`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct SystemKey(u64);
+
+impl SystemKey {
+    /// A key that no other system has.
+    #[must_use]
+    pub fn unique() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Identifies a system and the stage it was added to, returned by `App::add_system`.
+///
+/// Used to remove, enable or disable the system while the app is running.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SystemHandle {
+    pub stage: StageId,
+    pub key: SystemKey,
+}
 
 /// Manages multiple stages, each identified by a unique `StageTag`.
 ///
 /// The `Stages` struct provides methods to add, retrieve, and modify stages.
 #[derive(Default, Debug)]
 pub struct Stages {
-    pub stages: HashMap<TypeId, Stage>,
+    pub stages: HashMap<StageId, Stage>,
 }
 
 impl Stages {
@@ -60,14 +216,42 @@ impl Stages {
     /// let stage = Stage::new();
     /// stages.add::<UpdateStage>(stage);
     /// ```
-    pub fn add<S>(&mut self, stage: Stage)
+    pub fn add<S>(&mut self, mut stage: Stage)
     where
         S: StageTag,
     {
-        let stage_id = TypeId::of::<S>();
+        stage.name = type_name::<S>();
+        self.insert(StageId::of::<S>(), stage);
+    }
+
+    /// Adds a stage with the specified id, replacing any stage that already has that id.
+    pub fn insert(&mut self, stage_id: StageId, mut stage: Stage) {
+        stage.id = stage_id.clone();
         self.stages.insert(stage_id, stage);
     }
 
+    /// Retrieves a mutable reference to the stage with the specified id, adding an empty stage named `name` if it is missing.
+    pub fn get_or_insert_mut(&mut self, stage_id: StageId, name: &'static str) -> &mut Stage {
+        self.stages
+            .entry(stage_id.clone())
+            .or_insert_with(|| Stage {
+                name,
+                id: stage_id,
+                ..Stage::new()
+            })
+    }
+
+    /// Sorts the systems in all stages, see [`Stage::sort`].
+    ///
+    /// # Errors
+    /// The first [`StageError`] that is encountered.
+    pub fn sort(&mut self) -> Result<(), StageError> {
+        for stage in self.stages.values_mut() {
+            stage.sort()?;
+        }
+        Ok(())
+    }
+
     /// Retrieves a mutable reference to a stage identified by the specified tag.
     ///
     /// # Type Parameters
@@ -99,8 +283,7 @@ impl Stages {
     where
         S: StageTag,
     {
-        let stage_id = TypeId::of::<S>();
-        self.stages.get_mut(&stage_id)
+        self.stages.get_mut(&StageId::of::<S>())
     }
 
     /// Retrieves an immutable reference to a stage identified by the specified tag.
@@ -134,8 +317,7 @@ impl Stages {
     where
         S: StageTag,
     {
-        let stage_id = TypeId::of::<S>();
-        self.stages.get(&stage_id)
+        self.stages.get(&StageId::of::<S>())
     }
 
     /// Retrieves an immutable reference to a stage by its `TypeId`.
@@ -165,20 +347,67 @@ impl Stages {
     #[inline]
     #[must_use]
     pub fn get_by_id(&self, stage_id: &TypeId) -> Option<&Stage> {
+        self.stages.get(&StageId::from_type_id(*stage_id))
+    }
+
+    /// Retrieves an immutable reference to a stage by its [`StageId`], which also covers tags with a value.
+    #[inline]
+    #[must_use]
+    pub fn get_by_stage_id(&self, stage_id: &StageId) -> Option<&Stage> {
         self.stages.get(stage_id)
     }
+
+    #[inline]
+    #[must_use]
+    pub fn get_mut_by_stage_id(&mut self, stage_id: &StageId) -> Option<&mut Stage> {
+        self.stages.get_mut(stage_id)
+    }
+
+    /// Removes the system from its stage, see [`Stage::remove_system`].
+    ///
+    /// Returns `false` if the stage or the system is missing.
+    pub fn remove_system(&mut self, handle: &SystemHandle) -> bool {
+        self.stages
+            .get_mut(&handle.stage)
+            .is_some_and(|stage| stage.remove_system(handle.key))
+    }
+
+    /// See [`Stage::set_system_enabled`].
+    ///
+    /// Returns `false` if the stage or the system is missing.
+    pub fn set_system_enabled(&mut self, handle: &SystemHandle, enabled: bool) -> bool {
+        self.stages
+            .get_mut(&handle.stage)
+            .is_some_and(|stage| stage.set_system_enabled(handle.key, enabled))
+    }
 }
 
+/// Decides if the systems in a stage should run, see [`Stage::set_run_condition`].
+pub type StageRunCondition = dyn Fn(&State) -> bool;
+
 /// Represents a single stage containing a collection of systems.
 ///
 /// A `Stage` can have multiple systems that execute with access to shared state.
 pub struct Stage {
-    systems: Vec<Box<dyn System>>,
+    name: &'static str,
+    id: StageId,
+    systems: Vec<SystemConfig>,
+    keys: Vec<SystemKey>,
+    enabled: Vec<bool>,
+    accesses: Vec<Access>,
+    order: Vec<usize>,
+    predecessors: Vec<Vec<usize>>,
+    needs_sort: bool,
+    /// Labels that only removed systems had, so constraints on them are met.
+    removed_labels: Vec<&'static str>,
+    run_condition: Option<Box<StageRunCondition>>,
+    executor: ExecutorKind,
+    parallel_schedule: OnceCell<ParallelSchedule>,
 }
 
 impl Debug for Stage {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "stage")
+        write!(f, "{}", self.name)
     }
 }
 
@@ -209,33 +438,232 @@ impl Stage {
     /// ```
     #[must_use]
     pub fn new() -> Self {
-        Self { systems: vec![] }
+        Self {
+            name: "stage",
+            id: StageId::from_type_id(TypeId::of::<Self>()),
+            systems: vec![],
+            keys: vec![],
+            enabled: vec![],
+            accesses: vec![],
+            order: vec![],
+            predecessors: vec![],
+            needs_sort: false,
+            removed_labels: vec![],
+            run_condition: None,
+            executor: ExecutorKind::default(),
+            parallel_schedule: OnceCell::new(),
+        }
+    }
+
+    /// The type name of the stage tag, set when the stage is added to [`Stages`].
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The id of the stage, set when the stage is added to [`Stages`].
+    #[must_use]
+    pub const fn id(&self) -> &StageId {
+        &self.id
     }
 
     /// Adds a system to the stage.
     ///
-    /// Systems are the core units of logic that run within a stage. They are executed in the order they are added.
+    /// Systems are the core units of logic that run within a stage. They are executed in the order they are added,
+    /// unless they have ordering constraints, see [`Self::sort`].
     ///
     /// # Type Parameters
     ///
     /// - `F`: A function or closure that can be converted into a `System`.
-    /// - `Params`: Parameters required by the system, implementing the `SystemParam` trait.
+    /// - `Params`: Parameters required by the system, usually a tuple of `SystemParam`s.
     ///
     /// # Parameters
     ///
-    /// - `function`: The system function or closure to add.
+    /// - `function`: The system function or closure to add, optionally with run conditions, labels and ordering constraints.
+    ///
+    /// # Returns
     ///
-    pub fn add_system<F, Params>(&mut self, function: F)
+    /// The key used to remove, enable or disable the system later.
+    ///
+    pub fn add_system<F, Params>(&mut self, function: F) -> SystemKey
     where
-        F: IntoSystem<Params>,
-        Params: SystemParam,
+        F: IntoSystemConfig<Params>,
     {
-        self.systems.push(Box::new(function.into_system()));
+        self.insert_system(SystemKey::unique(), function.into_system_config())
+    }
+
+    /// Adds a system with a key from [`SystemKey::unique`], for when the key must be known before
+    /// the system can be added.
+    pub fn insert_system(&mut self, key: SystemKey, config: SystemConfig) -> SystemKey {
+        if !config.labels().is_empty()
+            || !config.runs_before().is_empty()
+            || !config.runs_after().is_empty()
+        {
+            // Other systems can be ordered against the labels
+            self.needs_sort = true;
+        }
+        self.removed_labels
+            .retain(|label| !config.labels().contains(label));
+        self.order.push(self.systems.len());
+        self.accesses.push(config.system().access());
+        self.predecessors.push(Vec::new());
+        self.systems.push(config);
+        self.keys.push(key);
+        self.enabled.push(true);
+        self.parallel_schedule = OnceCell::new();
+        key
+    }
+
+    /// Removes the system. The other systems keep their order.
+    ///
+    /// Constraints on labels that no other system has are met from now on, until a system
+    /// with the label is added again.
+    ///
+    /// Returns `false` if the stage has no system with that key.
+    pub fn remove_system(&mut self, key: SystemKey) -> bool {
+        let Some(index) = self.index_of(key) else {
+            return false;
+        };
+
+        let removed = self.systems.remove(index);
+        for &label in removed.labels() {
+            let still_labeled = self
+                .systems
+                .iter()
+                .any(|config| config.labels().contains(&label));
+            if !still_labeled && !self.removed_labels.contains(&label) {
+                self.removed_labels.push(label);
+            }
+        }
+        self.keys.remove(index);
+        self.enabled.remove(index);
+        self.accesses.remove(index);
+        self.predecessors.remove(index);
+
+        let moved = |other: usize| if other > index { other - 1 } else { other };
+        self.order.retain(|&other| other != index);
+        for other in &mut self.order {
+            *other = moved(*other);
+        }
+        for predecessors in &mut self.predecessors {
+            predecessors.retain(|&other| other != index);
+            for other in predecessors.iter_mut() {
+                *other = moved(*other);
+            }
+        }
+        self.parallel_schedule = OnceCell::new();
+
+        true
+    }
+
+    /// A disabled system keeps its place in the stage, but is skipped when the stage runs.
+    /// Systems are enabled when they are added.
+    ///
+    /// Returns `false` if the stage has no system with that key.
+    pub fn set_system_enabled(&mut self, key: SystemKey, enabled: bool) -> bool {
+        let Some(index) = self.index_of(key) else {
+            return false;
+        };
+        self.enabled[index] = enabled;
+        true
+    }
+
+    /// `None` if the stage has no system with that key.
+    #[must_use]
+    pub fn is_system_enabled(&self, key: SystemKey) -> Option<bool> {
+        self.index_of(key).map(|index| self.enabled[index])
+    }
+
+    /// The keys of the systems, in the order they were added.
+    #[must_use]
+    pub fn system_keys(&self) -> &[SystemKey] {
+        &self.keys
+    }
+
+    fn index_of(&self, key: SystemKey) -> Option<usize> {
+        self.keys.iter().position(|&other| other == key)
+    }
+
+    /// The systems, in the order they were added. [`Self::order`] and [`Self::predecessors`] index into it.
+    #[must_use]
+    pub fn systems(&self) -> &[SystemConfig] {
+        &self.systems
+    }
+
+    /// The indices of the systems, in the order they run, see [`Self::sort`].
+    #[must_use]
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// For each system, the systems that must run before it because of `before` and `after` constraints.
+    /// Only complete once the stage is sorted.
+    #[must_use]
+    pub fn predecessors(&self) -> &[Vec<usize>] {
+        &self.predecessors
+    }
+
+    #[must_use]
+    pub const fn has_run_condition(&self) -> bool {
+        self.run_condition.is_some()
+    }
+
+    /// The names of the systems, in the order they were added.
+    #[must_use]
+    pub fn system_names(&self) -> Vec<&'static str> {
+        self.systems
+            .iter()
+            .map(|config| config.system().name())
+            .collect()
+    }
+
+    /// Selects how the systems in the stage are run. The default is [`ExecutorKind::Serial`].
+    pub fn set_executor(&mut self, executor: ExecutorKind) {
+        self.executor = executor;
+        self.parallel_schedule = OnceCell::new();
+    }
+
+    #[must_use]
+    pub const fn executor(&self) -> ExecutorKind {
+        self.executor
+    }
+
+    /// The systems in the stage are only run when `condition` returns `true`.
+    ///
+    /// Replaces any previous run condition.
+    pub fn set_run_condition(&mut self, condition: impl Fn(&State) -> bool + 'static) {
+        self.run_condition = Some(Box::new(condition));
+    }
+
+    /// Orders the systems so that all `before` and `after` constraints are met.
+    /// Systems without constraints between them keep the order they were added in.
+    ///
+    /// Until the stage is sorted, the systems run in the order they were added.
+    ///
+    /// # Errors
+    /// If a constraint refers to a label that no system in the stage has or had, or if the constraints form a cycle.
+    pub fn sort(&mut self) -> Result<(), StageError> {
+        if !self.needs_sort {
+            return Ok(());
+        }
+
+        let sorted = sorted_order(self.name, &self.systems, &self.removed_labels)?;
+        self.order = sorted.order;
+        self.predecessors = sorted.predecessors;
+        self.needs_sort = false;
+        self.parallel_schedule = OnceCell::new();
+
+        Ok(())
     }
 
     /// Executes all systems within the stage, providing mutable access to the shared `State`.
     ///
-    /// Systems are run in the order they were added to the stage.
+    /// Systems are run in sorted order, unless the run condition returns `false`. Disabled systems are skipped.
+    /// With a parallel [`ExecutorKind`], systems that do not conflict can run at the same time.
+    /// Errors returned by systems are passed to the [`limnus_system::SystemErrorHandler`], right away
+    /// with the serial executor, and when all systems are done with the parallel ones.
+    /// Commands recorded by the systems are applied when all systems are done.
+    /// The stage and each system run in `tracing` spans, and are timed if the [`FrameDiagnostics`] resource exists.
     ///
     /// # Parameters
     ///
@@ -252,8 +680,94 @@ impl Stage {
     /// stage.run(&mut state);
     /// ```
     pub fn run(&self, state: &mut State) {
-        for system in &self.systems {
-            system.run(state);
+        if let Some(run_condition) = &self.run_condition
+            && !run_condition(state)
+        {
+            return;
         }
+
+        let _span = debug_span!("stage", name = self.name).entered();
+        let start = Instant::now();
+        let measure = state.resources().get::<FrameDiagnostics>().is_some();
+
+        let deterministic = match self.executor {
+            ExecutorKind::Serial => None,
+            ExecutorKind::Parallel => Some(false),
+            ExecutorKind::DeterministicParallel => Some(true),
+        };
+
+        match deterministic {
+            Some(deterministic) if self.systems.len() > 1 => {
+                let schedule = self.parallel_schedule.get_or_init(|| {
+                    ParallelSchedule::new(
+                        &self.order,
+                        &self.predecessors,
+                        &self.accesses,
+                        deterministic,
+                    )
+                });
+                let run = run_parallel(
+                    &self.systems,
+                    &self.accesses,
+                    &self.enabled,
+                    schedule,
+                    state,
+                );
+                // Systems that were skipped for a missing param are reported now that no system is running
+                for (index, error) in run.errors {
+                    if let Err(error) = handle_missing_param(state, Err(error)) {
+                        self.handle_error(state, index, error);
+                    }
+                }
+                if measure {
+                    for &index in self.order.iter().filter(|&&index| self.enabled[index]) {
+                        self.record_system(state, index, run.durations[index]);
+                    }
+                }
+            }
+            _ => {
+                for &index in self.order.iter().filter(|&&index| self.enabled[index]) {
+                    let system = self.systems[index].system();
+                    let system_start = Instant::now();
+                    let result = {
+                        let _span = debug_span!("system", name = system.name()).entered();
+                        system.run(state)
+                    };
+                    if measure {
+                        self.record_system(state, index, system_start.elapsed());
+                    }
+                    if let Err(error) = result {
+                        self.handle_error(state, index, error);
+                    }
+                }
+            }
+        }
+
+        state.apply_commands();
+
+        if measure && let Some(diagnostics) = state.resource_mut::<FrameDiagnostics>() {
+            diagnostics.record_stage(&self.id, self.name, start.elapsed());
+        }
+    }
+
+    /// The diagnostics can have been removed by a system, so they are looked up each time.
+    fn record_system(&self, state: &mut State, index: usize, duration: Duration) {
+        if let Some(diagnostics) = state.resource_mut::<FrameDiagnostics>() {
+            diagnostics.record_system(
+                &self.id,
+                self.name,
+                self.systems[index].system().name(),
+                duration,
+            );
+        }
+    }
+
+    fn handle_error(&self, state: &State, index: usize, error: BoxedError) {
+        let error = SystemError {
+            system: self.systems[index].system().name(),
+            stage: self.name,
+            error,
+        };
+        handle_system_error(state, error);
     }
 }
@@ -0,0 +1,152 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_system::SystemConfig;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum StageError {
+    UnknownLabel {
+        stage: &'static str,
+        system: &'static str,
+        label: &'static str,
+    },
+    /// The systems, in the order they would have to run, where the last one must run before the first one.
+    Cycle {
+        stage: &'static str,
+        systems: Vec<&'static str>,
+    },
+}
+
+impl Display for StageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownLabel {
+                stage,
+                system,
+                label,
+            } => write!(
+                f,
+                "system `{system}` in stage `{stage}` is ordered against label `{label}`, which no system in the stage has"
+            ),
+            Self::Cycle { stage, systems } => {
+                write!(f, "systems in stage `{stage}` have cyclic ordering: ")?;
+                for system in systems {
+                    write!(f, "`{system}` -> ")?;
+                }
+                write!(f, "`{}`", systems[0])
+            }
+        }
+    }
+}
+
+impl std::error::Error for StageError {}
+
+pub(crate) struct SortedSystems {
+    pub order: Vec<usize>,
+    /// For each system, the systems that must have completed before it can run.
+    pub predecessors: Vec<Vec<usize>>,
+}
+
+/// Topological sort of the systems. Ties are broken by the order the systems were added in.
+///
+/// Constraints on `removed_labels` that no system has any more are met.
+pub(crate) fn sorted_order(
+    stage: &'static str,
+    systems: &[SystemConfig],
+    removed_labels: &[&'static str],
+) -> Result<SortedSystems, StageError> {
+    let count = systems.len();
+    let mut successors = vec![Vec::new(); count];
+    let mut predecessors = vec![Vec::new(); count];
+
+    let labeled = |label: &'static str, system_index: usize| {
+        let indices: Vec<usize> = systems
+            .iter()
+            .enumerate()
+            .filter(|(_, config)| config.labels().contains(&label))
+            .map(|(index, _)| index)
+            .collect();
+        if indices.is_empty() && !removed_labels.contains(&label) {
+            Err(StageError::UnknownLabel {
+                stage,
+                system: systems[system_index].system().name(),
+                label,
+            })
+        } else {
+            Ok(indices)
+        }
+    };
+
+    for (index, config) in systems.iter().enumerate() {
+        for &label in config.runs_before() {
+            for other in labeled(label, index)? {
+                if other != index {
+                    successors[index].push(other);
+                    predecessors[other].push(index);
+                }
+            }
+        }
+        for &label in config.runs_after() {
+            for other in labeled(label, index)? {
+                if other != index {
+                    successors[other].push(index);
+                    predecessors[index].push(other);
+                }
+            }
+        }
+    }
+
+    let mut waiting_for: Vec<usize> = predecessors.iter().map(Vec::len).collect();
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..count)
+        .filter(|&index| waiting_for[index] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(count);
+
+    while let Some(Reverse(index)) = ready.pop() {
+        order.push(index);
+        for &successor in &successors[index] {
+            waiting_for[successor] -= 1;
+            if waiting_for[successor] == 0 {
+                ready.push(Reverse(successor));
+            }
+        }
+    }
+
+    if order.len() == count {
+        return Ok(SortedSystems {
+            order,
+            predecessors,
+        });
+    }
+
+    // Every system that is left waits for another system that is left, so walking
+    // backwards through the predecessors must eventually revisit a system.
+    let mut path = Vec::new();
+    let mut current = (0..count)
+        .find(|&index| waiting_for[index] > 0)
+        .expect("a system must be left");
+    let cycle_start = loop {
+        if let Some(position) = path.iter().position(|&index| index == current) {
+            break position;
+        }
+        path.push(current);
+        current = *predecessors[current]
+            .iter()
+            .find(|&&index| waiting_for[index] > 0)
+            .expect("a system that is left must wait for another system that is left");
+    };
+
+    Err(StageError::Cycle {
+        stage,
+        systems: path[cycle_start..]
+            .iter()
+            .rev()
+            .map(|&index| systems[index].system().name())
+            .collect(),
+    })
+}
@@ -0,0 +1,116 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_macros::Resource;
+use limnus_resource::Resource;
+use limnus_stage::{Stage, StageError, StageTag, Stages};
+use limnus_system::IntoSystem;
+use limnus_system_params::ReM;
+use limnus_system_state::State;
+
+#[derive(Debug, Default, Resource)]
+struct Log {
+    entries: Vec<&'static str>,
+}
+
+fn first(mut log: ReM<Log>) {
+    log.entries.push("first");
+}
+
+fn second(mut log: ReM<Log>) {
+    log.entries.push("second");
+}
+
+fn third(mut log: ReM<Log>) {
+    log.entries.push("third");
+}
+
+fn fourth(mut log: ReM<Log>) {
+    log.entries.push("fourth");
+}
+
+fn run(stage: &Stage) -> Vec<&'static str> {
+    let mut state = State::new();
+    state.resources_mut().insert(Log::default());
+    stage.run(&mut state);
+    state.resources_mut().remove::<Log>().unwrap().entries
+}
+
+#[test]
+fn insertion_order_without_constraints() {
+    let mut stage = Stage::new();
+    stage.add_system(first);
+    stage.add_system(second);
+    stage.add_system(third);
+
+    stage.sort().unwrap();
+
+    assert_eq!(run(&stage), ["first", "second", "third"]);
+}
+
+#[test]
+fn before_and_after() {
+    let mut stage = Stage::new();
+    stage.add_system(third.label("third").after("second"));
+    stage.add_system(fourth);
+    stage.add_system(second.label("second"));
+    stage.add_system(first.before("second"));
+
+    stage.sort().unwrap();
+
+    // `fourth` has no constraints and was added before `first`
+    assert_eq!(run(&stage), ["fourth", "first", "second", "third"]);
+}
+
+#[test]
+fn shared_label() {
+    let mut stage = Stage::new();
+    stage.add_system(third.after("early"));
+    stage.add_system(second.label("early"));
+    stage.add_system(first.label("early"));
+
+    stage.sort().unwrap();
+
+    assert_eq!(run(&stage), ["second", "first", "third"]);
+}
+
+#[test]
+fn unknown_label() {
+    let mut stage = Stage::new();
+    stage.add_system(first.before("missing"));
+
+    let err = stage.sort().unwrap_err();
+
+    assert!(matches!(
+        err,
+        StageError::UnknownLabel {
+            label: "missing",
+            ..
+        }
+    ));
+    assert!(err.to_string().contains("ordering::first"));
+}
+
+#[test]
+fn cycle() {
+    struct Update;
+    impl StageTag for Update {}
+
+    let mut stages = Stages::new();
+    stages.add::<Update>(Stage::new());
+    let stage = stages.get_mut::<Update>().unwrap();
+    stage.add_system(fourth);
+    stage.add_system(first.label("first").after("third"));
+    stage.add_system(second.label("second").after("first"));
+    stage.add_system(third.label("third").after("second"));
+
+    let err = stages.sort().unwrap_err();
+
+    let StageError::Cycle { stage, systems } = &err else {
+        panic!("expected a cycle, got {err:?}");
+    };
+    assert!(stage.ends_with("Update"));
+    assert_eq!(systems.len(), 3);
+    assert!(err.to_string().contains("have cyclic ordering"));
+}
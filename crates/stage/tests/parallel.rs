@@ -0,0 +1,237 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_local_resource::LocalResource;
+use limnus_macros::{LocalResource, Resource};
+use limnus_resource::Resource;
+use limnus_stage::{ExecutorKind, Stage};
+use limnus_system::{IntoSystem, MissingParamPolicy, MissingParams, System};
+use limnus_system_params::{LoReM, Re, ReAll, ReM};
+use limnus_system_state::State;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default, Resource)]
+struct Gate {
+    arrived: AtomicUsize,
+}
+
+impl Gate {
+    /// Returns true if `count` systems arrived at the gate at the same time.
+    fn meet(&self, count: usize) -> bool {
+        self.arrived.fetch_add(1, Ordering::SeqCst);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if self.arrived.load(Ordering::SeqCst) >= count {
+                return true;
+            }
+            thread::yield_now();
+        }
+        false
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+struct Left {
+    met: bool,
+}
+
+#[derive(Debug, Default, Resource)]
+struct Right {
+    met: bool,
+}
+
+#[derive(Debug, Default, Resource)]
+struct Log {
+    entries: Vec<&'static str>,
+}
+
+#[derive(Debug, Default, LocalResource)]
+struct MainThreadLog {
+    threads: Vec<ThreadId>,
+}
+
+fn left(gate: Re<Gate>, mut left: ReM<Left>) {
+    left.met = gate.meet(2);
+}
+
+fn right(gate: Re<Gate>, mut right: ReM<Right>) {
+    right.met = gate.meet(2);
+}
+
+fn first(mut log: ReM<Log>) {
+    log.entries.push("first");
+}
+
+fn second(mut log: ReM<Log>) {
+    log.entries.push("second");
+}
+
+fn third(mut log: ReM<Log>) {
+    log.entries.push("third");
+}
+
+fn reader(_left: Re<Left>) {}
+
+fn local(mut main_thread_log: LoReM<MainThreadLog>) {
+    main_thread_log.threads.push(thread::current().id());
+}
+
+fn state() -> State {
+    let mut state = State::new();
+    state.resources_mut().insert(Gate::default());
+    state.resources_mut().insert(Left::default());
+    state.resources_mut().insert(Right::default());
+    state.resources_mut().insert(Log::default());
+    state.local_resources_mut().insert(MainThreadLog::default());
+    state
+}
+
+#[test]
+fn compatible_systems_run_at_the_same_time() {
+    let mut stage = Stage::new();
+    stage.set_executor(ExecutorKind::Parallel);
+    stage.add_system(left);
+    stage.add_system(right);
+
+    let mut state = state();
+    stage.run(&mut state);
+
+    assert!(state.resource::<Left>().met);
+    assert!(state.resource::<Right>().met);
+}
+
+#[test]
+fn conditional_systems_run_at_the_same_time() {
+    fn has_log(_log: Re<Log>) -> bool {
+        true
+    }
+
+    let mut stage = Stage::new();
+    stage.set_executor(ExecutorKind::Parallel);
+    stage.add_system(left.run_if(has_log));
+    stage.add_system(right.run_if(has_log));
+
+    let mut state = state();
+    stage.run(&mut state);
+
+    assert!(state.resource::<Left>().met);
+    assert!(state.resource::<Right>().met);
+}
+
+#[test]
+fn systems_outside_stages_do_not_need_to_be_send() {
+    let runs = Rc::new(Cell::new(0));
+    let counted_runs = Rc::clone(&runs);
+    let system = (move |_log: Re<Log>| counted_runs.set(counted_runs.get() + 1)).into_system();
+
+    let mut state = state();
+    system.run(&mut state).unwrap();
+    system.run(&mut state).unwrap();
+
+    assert_eq!(runs.get(), 2);
+}
+
+#[test]
+fn missing_params_are_reported_after_the_parallel_systems() {
+    #[derive(Debug, Resource)]
+    struct Missing;
+
+    fn needs_missing(_missing: Re<Missing>) {}
+
+    fn inserts_log(mut resources: ReAll) {
+        resources.insert(Log::default());
+    }
+
+    let mut stage = Stage::new();
+    stage.set_executor(ExecutorKind::Parallel);
+    stage.add_system(needs_missing);
+    stage.add_system(inserts_log);
+
+    let mut state = state();
+    state
+        .resources_mut()
+        .insert(MissingParams::new(MissingParamPolicy::Count));
+    stage.run(&mut state);
+    stage.run(&mut state);
+
+    let name = std::any::type_name_of_val(&needs_missing);
+    assert_eq!(state.resource::<MissingParams>().count(name), 2);
+}
+
+#[test]
+fn ordering_constraints_are_kept() {
+    let mut stage = Stage::new();
+    stage.set_executor(ExecutorKind::Parallel);
+    stage.add_system(third.after("second"));
+    stage.add_system(second.label("second").after("first"));
+    stage.add_system(first.label("first"));
+    stage.sort().unwrap();
+
+    let mut state = state();
+    stage.run(&mut state);
+
+    assert_eq!(
+        state.resource::<Log>().entries,
+        ["first", "second", "third"]
+    );
+}
+
+#[test]
+fn deterministic_is_same_as_serial() {
+    let mut stage = Stage::new();
+    stage.set_executor(ExecutorKind::DeterministicParallel);
+    stage.add_system(second);
+    stage.add_system(reader);
+    stage.add_system(third);
+    stage.add_system(reader);
+    stage.add_system(first);
+
+    let mut state = state();
+    for _ in 0..20 {
+        stage.run(&mut state);
+    }
+
+    let entries = &state.resource::<Log>().entries;
+    assert_eq!(entries.len(), 60);
+    for frame in entries.chunks(3) {
+        assert_eq!(frame, ["second", "third", "first"]);
+    }
+}
+
+#[test]
+fn local_resource_systems_run_on_the_calling_thread() {
+    let mut stage = Stage::new();
+    stage.set_executor(ExecutorKind::Parallel);
+    stage.add_system(local);
+    stage.add_system(first);
+    stage.add_system(local);
+    stage.add_system(reader);
+
+    let mut state = state();
+    stage.run(&mut state);
+
+    let threads = &state.local_resource::<MainThreadLog>().unwrap().threads;
+    assert_eq!(threads, &[thread::current().id(), thread::current().id()]);
+}
+
+#[test]
+#[should_panic(expected = "system failed")]
+fn panics_are_propagated() {
+    fn failing(_left: ReM<Left>) {
+        panic!("system failed");
+    }
+
+    let mut stage = Stage::new();
+    stage.set_executor(ExecutorKind::Parallel);
+    stage.add_system(first);
+    stage.add_system(failing);
+    stage.add_system(second);
+
+    let mut state = state();
+    stage.run(&mut state);
+}
@@ -0,0 +1,33 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::{Msg, Re};
+use limnus_message::Message;
+use limnus_resource::Resource;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Condition that is true if the resource `R` has been inserted.
+///
+/// It only reads `R`, so the parallel executors can run it next to systems that use other resources.
+#[must_use]
+pub fn resource_exists<R: Resource>(resource: Option<Re<R>>) -> bool {
+    resource.is_some()
+}
+
+/// Condition that is true if messages of type `M` can be read this frame,
+/// the same messages as returned by [`limnus_message::Messages::iter_previous`].
+#[must_use]
+pub fn message_sent<M: Message>(messages: Msg<M>) -> bool {
+    !messages.is_empty_previous()
+}
+
+/// Condition that is true every `n`th time it is evaluated, starting with the `n`th time.
+///
+/// # Panics
+/// If `n` is zero.
+pub fn every_n_ticks(n: u64) -> impl Fn() -> bool + 'static {
+    assert!(n > 0, "every_n_ticks needs a tick count of at least one");
+    let ticks = AtomicU64::new(0);
+    move || (ticks.fetch_add(1, Ordering::Relaxed) + 1).is_multiple_of(n)
+}
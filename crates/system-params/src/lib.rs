@@ -2,16 +2,22 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
+mod conditions;
 pub mod prelude;
 
+pub use conditions::{every_n_ticks, message_sent, resource_exists};
+
 use limnus_local_resource::{LocalResource, LocalResourceStorage};
 use limnus_message::{Message, MessageStorage, Messages};
 use limnus_resource::{Resource, ResourceStorage};
-use limnus_system::SystemParam;
-use limnus_system_state::State;
-use std::mem::transmute;
+use limnus_system::{Access, SystemParam};
+use limnus_system_state::StateCell;
 use std::ops::{Deref, DerefMut};
 
+// The params borrow a single type, or a whole storage, from the `StateCell`, never the whole `State`.
+// They are only fetched together with params whose access does not conflict, which is checked
+// when a system is created, and by the parallel executors between the systems in a stage.
+
 // Mutable resource access
 pub struct ReM<'a, T: 'static> {
     value: &'a mut T,
@@ -58,41 +64,57 @@ impl<T> Deref for Re<'_, T> {
 
 impl<T: Resource + 'static> SystemParam for ReM<'static, T> {
     type Item = Self;
+    type Local = ();
+
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let resource = state.resources().get_ptr::<T>()?;
+        Some(ReM::new(unsafe { &mut *resource }))
+    }
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref = world.resource_mut::<T>()?;
-        let static_ref: &'static mut T = unsafe { transmute(actual_ref) };
-        Some(ReM::new(static_ref))
+    fn access(access: &mut Access) {
+        access.resources_mut().add_write::<T>();
     }
 }
 
 impl<T: Resource + 'static> SystemParam for Re<'static, T> {
     type Item = Self;
+    type Local = ();
+
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let resource = state.resources().get_ptr::<T>()?;
+        Some(Re::new(unsafe { &*resource }))
+    }
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref = world.resource_mut::<T>()?;
-        let static_ref: &'static mut T = unsafe { transmute(actual_ref) };
-        Some(Re::new(static_ref))
+    fn access(access: &mut Access) {
+        access.resources_mut().add_read::<T>();
     }
 }
 
 impl<T: 'static + Message> SystemParam for Msg<'static, T> {
     type Item = Self;
+    type Local = ();
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref = world.message::<T>()?;
-        let static_ref: &'static Messages<T> = unsafe { transmute(actual_ref) };
-        Some(Msg::new(static_ref))
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let messages = state.messages().get_ptr::<T>()?;
+        Some(Msg::new(unsafe { &*messages }))
+    }
+
+    fn access(access: &mut Access) {
+        access.messages_mut().add_read::<T>();
     }
 }
 
 impl<T: 'static + Message> SystemParam for MsgM<'static, T> {
     type Item = Self;
+    type Local = ();
+
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let messages = state.messages().get_ptr::<T>()?;
+        Some(MsgM::new(unsafe { &mut *messages }))
+    }
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref = world.message_mut::<T>()?;
-        let static_ref: &'static mut Messages<T> = unsafe { transmute(actual_ref) };
-        Some(MsgM::new(static_ref))
+    fn access(access: &mut Access) {
+        access.messages_mut().add_write::<T>();
     }
 }
 
@@ -121,11 +143,15 @@ impl DerefMut for ReAll<'_> {
 
 impl SystemParam for ReAll<'static> {
     type Item = Self;
+    type Local = ();
+
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let storage: *mut ResourceStorage = unsafe { state.resources_mut() };
+        Some(ReAll::new(unsafe { &mut *storage }))
+    }
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref: &mut ResourceStorage = world.resources_mut();
-        let static_ref: &'static mut ResourceStorage = unsafe { transmute(actual_ref) };
-        Some(ReAll::new(static_ref))
+    fn access(access: &mut Access) {
+        access.resources_mut().add_write_all();
     }
 }
 
@@ -200,11 +226,15 @@ impl DerefMut for MsgAll<'_> {
 
 impl SystemParam for MsgAll<'static> {
     type Item = Self;
+    type Local = ();
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref: &mut MessageStorage = world.messages_mut();
-        let static_ref: &'static mut MessageStorage = unsafe { transmute(actual_ref) };
-        Some(MsgAll::new(static_ref))
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let storage: *mut MessageStorage = unsafe { state.messages_mut() };
+        Some(MsgAll::new(unsafe { &mut *storage }))
+    }
+
+    fn access(access: &mut Access) {
+        access.messages_mut().add_write_all();
     }
 }
 
@@ -235,11 +265,15 @@ impl DerefMut for LoReAll<'_> {
 
 impl SystemParam for LoReAll<'static> {
     type Item = Self;
+    type Local = ();
+
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let storage: *mut LocalResourceStorage = unsafe { state.local_resources_mut() };
+        Some(LoReAll::new(unsafe { &mut *storage }))
+    }
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref: &mut LocalResourceStorage = world.local_resources_mut();
-        let static_ref: &'static mut LocalResourceStorage = unsafe { transmute(actual_ref) };
-        Some(LoReAll::new(static_ref))
+    fn access(access: &mut Access) {
+        access.local_resources_mut().add_write_all();
     }
 }
 
@@ -272,11 +306,15 @@ impl<T> DerefMut for LoReM<'_, T> {
 
 impl<T: LocalResource + 'static> SystemParam for LoReM<'static, T> {
     type Item = Self;
+    type Local = ();
+
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let resource = state.local_resources().get_ptr::<T>()?;
+        Some(LoReM::new(unsafe { &mut *resource }))
+    }
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref = world.local_resource_mut::<T>()?;
-        let static_ref: &'static mut T = unsafe { transmute(actual_ref) };
-        Some(LoReM::new(static_ref))
+    fn access(access: &mut Access) {
+        access.local_resources_mut().add_write::<T>();
     }
 }
 
@@ -301,10 +339,54 @@ impl<T> Deref for LoRe<'_, T> {
 
 impl<T: LocalResource + 'static> SystemParam for LoRe<'static, T> {
     type Item = Self;
+    type Local = ();
+
+    unsafe fn get(state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
+        let resource = state.local_resources().get_ptr::<T>()?;
+        Some(LoRe::new(unsafe { &*resource }))
+    }
+
+    fn access(access: &mut Access) {
+        access.local_resources_mut().add_read::<T>();
+    }
+}
+
+/// Data owned by the system instance that has the param, starting at `T::default()`.
+///
+/// Each instance of a system has its own value, so adding the same function twice gives two separate values.
+pub struct Local<'a, T: 'static> {
+    value: &'a mut T,
+}
+
+impl<'a, T> Local<'a, T> {
+    pub fn new(value: &'a mut T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> Deref for Local<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<T> DerefMut for Local<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.value
+    }
+}
+
+impl<T: Default + 'static> SystemParam for Local<'static, T> {
+    type Item = Self;
+    type Local = T;
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        let actual_ref = world.local_resource_mut::<T>()?;
-        let static_ref: &'static mut T = unsafe { transmute(actual_ref) };
-        Some(LoRe::new(static_ref))
+    unsafe fn get(_state: StateCell<'_>, local: &mut T) -> Option<Self::Item> {
+        let local: *mut T = local;
+        Some(Local::new(unsafe { &mut *local }))
     }
+
+    /// The value is only reachable from the system that owns it.
+    fn access(_access: &mut Access) {}
 }
@@ -2,4 +2,7 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
-pub use crate::{LoRe, LoReAll, LoReM, Msg, MsgAll, MsgM, Re, ReAll, ReM};
+pub use crate::{
+    LoRe, LoReAll, LoReM, Local, Msg, MsgAll, MsgM, Re, ReAll, ReM, every_n_ticks, message_sent,
+    resource_exists,
+};
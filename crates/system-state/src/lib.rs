@@ -5,12 +5,57 @@
 use limnus_local_resource::{LocalResource, LocalResourceStorage};
 use limnus_message::{Message, MessageStorage, Messages};
 use limnus_resource::{Resource, ResourceStorage};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::mem::take;
+use std::sync::Mutex;
+
+/// A deferred change to the `State`, see [`CommandQueue`].
+pub type Command = Box<dyn FnOnce(&mut State) + Send>;
+
+/// Changes recorded while systems are running, that are applied with [`State::apply_commands`].
+///
+/// Commands can be pushed through a shared reference, so systems that run in parallel can record them.
+#[derive(Default)]
+pub struct CommandQueue {
+    commands: Mutex<Vec<Command>>,
+}
+
+impl Debug for CommandQueue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CommandQueue({})", self.len())
+    }
+}
+
+impl CommandQueue {
+    pub fn push(&self, command: impl FnOnce(&mut State) + Send + 'static) {
+        self.commands
+            .lock()
+            .expect("command queue lock")
+            .push(Box::new(command));
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.commands.lock().expect("command queue lock").len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn take(&self) -> Vec<Command> {
+        take(&mut *self.commands.lock().expect("command queue lock"))
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct State {
     resources: ResourceStorage,
     local_resources: LocalResourceStorage,
     messages: MessageStorage,
+    commands: CommandQueue,
 }
 
 impl State {
@@ -20,6 +65,26 @@ impl State {
             resources: ResourceStorage::new(),
             messages: MessageStorage::new(),
             local_resources: LocalResourceStorage::new(),
+            commands: CommandQueue::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn commands(&self) -> &CommandQueue {
+        &self.commands
+    }
+
+    /// Applies the recorded commands in the order they were recorded, including any commands
+    /// that they record in turn.
+    pub fn apply_commands(&mut self) {
+        loop {
+            let commands = self.commands.take();
+            if commands.is_empty() {
+                break;
+            }
+            for command in commands {
+                command(self);
+            }
         }
     }
 
@@ -47,6 +112,13 @@ impl State {
         &mut self.local_resources
     }
 
+    /// Both storages at the same time, for code that converts data from one into the other.
+    pub fn resources_and_local_resources_mut(
+        &mut self,
+    ) -> (&mut ResourceStorage, &mut LocalResourceStorage) {
+        (&mut self.resources, &mut self.local_resources)
+    }
+
     #[must_use]
     pub const fn local_resources(&self) -> &LocalResourceStorage {
         &self.local_resources
@@ -82,4 +154,100 @@ impl State {
     pub fn message<M: Message>(&self) -> Option<&Messages<M>> {
         self.messages.get::<M>()
     }
+
+    /// Drops the pending commands, then the local resources, then the resources and last the messages.
+    ///
+    /// Within each storage, the most recently inserted resource is dropped first, so a resource
+    /// is always dropped before the resources that were inserted before it.
+    pub fn clear(&mut self) {
+        drop(self.commands.take());
+        self.local_resources.clear();
+        self.resources.clear();
+        self.messages = MessageStorage::new();
+    }
+}
+
+/// A `State` that system params are fetched from, also by systems that run at the same time.
+///
+/// Each storage is reached without going through a `&mut State`, so params that borrow different
+/// storages, or different types in a storage through the `get_ptr` functions, do not alias.
+/// Only the functions that hand out mutable references are unsafe.
+#[derive(Clone, Copy)]
+pub struct StateCell<'a> {
+    state: *mut State,
+    marker: PhantomData<&'a mut State>,
+}
+
+// SAFETY: Sharing the cell between threads is up to the unsafe functions, and the callers that
+// dereference the `get_ptr` pointers, to do without conflicting borrows.
+unsafe impl Send for StateCell<'_> {}
+unsafe impl Sync for StateCell<'_> {}
+
+impl<'a> StateCell<'a> {
+    pub fn new(state: &'a mut State) -> Self {
+        Self {
+            state,
+            marker: PhantomData,
+        }
+    }
+
+    /// The same cell without a lifetime, for handing it to threads that are not scoped to the borrow.
+    ///
+    /// # Safety
+    /// The returned cell may not be used after `'a` has ended.
+    #[must_use]
+    pub const unsafe fn into_static(self) -> StateCell<'static> {
+        StateCell {
+            state: self.state,
+            marker: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn resources(self) -> &'a ResourceStorage {
+        unsafe { &(*self.state).resources }
+    }
+
+    #[must_use]
+    pub fn local_resources(self) -> &'a LocalResourceStorage {
+        unsafe { &(*self.state).local_resources }
+    }
+
+    #[must_use]
+    pub fn messages(self) -> &'a MessageStorage {
+        unsafe { &(*self.state).messages }
+    }
+
+    #[must_use]
+    pub fn commands(self) -> &'a CommandQueue {
+        unsafe { &(*self.state).commands }
+    }
+
+    /// # Safety
+    /// Nothing else may borrow the resource storage, or a resource in it, while the reference is alive.
+    #[must_use]
+    pub unsafe fn resources_mut(self) -> &'a mut ResourceStorage {
+        unsafe { &mut (*self.state).resources }
+    }
+
+    /// # Safety
+    /// Nothing else may borrow the local resource storage, or a resource in it, while the reference is alive.
+    #[must_use]
+    pub unsafe fn local_resources_mut(self) -> &'a mut LocalResourceStorage {
+        unsafe { &mut (*self.state).local_resources }
+    }
+
+    /// # Safety
+    /// Nothing else may borrow the message storage, or the messages in it, while the reference is alive.
+    #[must_use]
+    pub unsafe fn messages_mut(self) -> &'a mut MessageStorage {
+        unsafe { &mut (*self.state).messages }
+    }
+
+    /// # Safety
+    /// Nothing else may borrow anything in the `State` while the reference is alive.
+    #[must_use]
+    pub unsafe fn state_mut(self) -> &'a mut State {
+        unsafe { &mut *self.state }
+    }
 }
@@ -0,0 +1,301 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use core::any::{TypeId, type_name};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// The types a system reads and writes in one of the storages in `State`.
+#[derive(Debug, Default, Clone)]
+pub struct StorageAccess {
+    reads: HashMap<TypeId, &'static str>,
+    writes: HashMap<TypeId, &'static str>,
+    reads_all: bool,
+    writes_all: bool,
+    /// Types that were added by more than one param, where at least one of them writes. `None` is the whole storage.
+    conflicts: Vec<Option<&'static str>>,
+}
+
+impl StorageAccess {
+    pub fn add_read<T: 'static>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.writes_all {
+            self.conflicts.push(None);
+        } else if self.writes.contains_key(&type_id) {
+            self.conflicts.push(Some(type_name::<T>()));
+        }
+        self.reads.insert(type_id, type_name::<T>());
+    }
+
+    pub fn add_write<T: 'static>(&mut self) {
+        let type_id = TypeId::of::<T>();
+        if self.reads_all || self.writes_all {
+            self.conflicts.push(None);
+        } else if self.reads.contains_key(&type_id) || self.writes.contains_key(&type_id) {
+            self.conflicts.push(Some(type_name::<T>()));
+        }
+        self.writes.insert(type_id, type_name::<T>());
+    }
+
+    pub fn add_read_all(&mut self) {
+        if self.writes_any() {
+            self.conflicts.push(None);
+        }
+        self.reads_all = true;
+    }
+
+    pub fn add_write_all(&mut self) {
+        if !self.is_empty() {
+            self.conflicts.push(None);
+        }
+        self.writes_all = true;
+    }
+
+    /// Adds everything from `other` without checking for conflicts, since it was borrowed at another time,
+    /// like the params of a condition and the params of the system it guards.
+    pub fn extend(&mut self, other: &Self) {
+        self.reads.extend(other.reads.iter());
+        self.writes.extend(other.writes.iter());
+        self.reads_all |= other.reads_all;
+        self.writes_all |= other.writes_all;
+        self.conflicts.extend(other.conflicts.iter());
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.reads.is_empty() && self.writes.is_empty() && !self.reads_all && !self.writes_all
+    }
+
+    /// Type names of the types that are read, but not written.
+    #[must_use]
+    pub fn read_names(&self) -> Vec<&'static str> {
+        self.reads
+            .iter()
+            .filter(|(type_id, _)| !self.writes.contains_key(type_id))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    #[must_use]
+    pub fn write_names(&self) -> Vec<&'static str> {
+        self.writes.values().copied().collect()
+    }
+
+    #[must_use]
+    pub const fn reads_all(&self) -> bool {
+        self.reads_all
+    }
+
+    #[must_use]
+    pub const fn writes_all(&self) -> bool {
+        self.writes_all
+    }
+
+    fn writes_any(&self) -> bool {
+        self.writes_all || !self.writes.is_empty()
+    }
+
+    /// Two accesses are compatible if neither of them writes something that the other one reads or writes.
+    #[must_use]
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        if self.writes_all {
+            return other.is_empty();
+        }
+        if other.writes_all {
+            return self.is_empty();
+        }
+        if self.reads_all && other.writes_any() {
+            return false;
+        }
+        if other.reads_all && self.writes_any() {
+            return false;
+        }
+
+        !self
+            .writes
+            .keys()
+            .any(|type_id| other.reads.contains_key(type_id) || other.writes.contains_key(type_id))
+            && !other
+                .writes
+                .keys()
+                .any(|type_id| self.reads.contains_key(type_id))
+    }
+}
+
+/// The storages in `State`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Storage {
+    Resources,
+    LocalResources,
+    Messages,
+    CommandQueue,
+}
+
+impl Display for Storage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Resources => write!(f, "resource"),
+            Self::LocalResources => write!(f, "local resource"),
+            Self::Messages => write!(f, "message"),
+            Self::CommandQueue => write!(f, "command queue"),
+        }
+    }
+}
+
+/// Two params of the same system borrow the same data, and at least one of them borrows it mutably.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct AccessConflict {
+    pub storage: Storage,
+    /// The type that is borrowed more than once, or `None` if one of the params borrows the whole storage.
+    pub type_name: Option<&'static str>,
+}
+
+impl Display for AccessConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.type_name {
+            None if self.storage == Storage::CommandQueue => write!(
+                f,
+                "the command queue is borrowed by one param, and the whole state by another param"
+            ),
+            Some(type_name) => write!(
+                f,
+                "{} `{type_name}` is borrowed by more than one param, and at least one of them is mutable",
+                self.storage
+            ),
+            None => write!(
+                f,
+                "the {} storage is borrowed as a whole by one param, and also by another param",
+                self.storage
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AccessConflict {}
+
+/// Everything a system reads and writes in `State`, collected from its params with `SystemParam::access`.
+///
+/// Systems with compatible access can run at the same time.
+#[derive(Debug, Default, Clone)]
+pub struct Access {
+    resources: StorageAccess,
+    local_resources: StorageAccess,
+    messages: StorageAccess,
+    reads_commands: bool,
+    exclusive: bool,
+    /// An exclusive param and a param that reads the command queue were both added.
+    commands_conflict: bool,
+}
+
+impl Access {
+    /// Reads and writes everything and must run on the main thread.
+    /// Used for systems and params that do not declare what they access.
+    #[must_use]
+    pub fn exclusive() -> Self {
+        let mut access = Self::default();
+        access.add_exclusive();
+        access
+    }
+
+    /// Borrows the whole `State`, so it is not compatible with any other access, not even an empty one.
+    pub fn add_exclusive(&mut self) {
+        self.resources.add_write_all();
+        self.local_resources.add_write_all();
+        self.messages.add_write_all();
+        self.commands_conflict |= self.reads_commands;
+        self.exclusive = true;
+    }
+
+    #[must_use]
+    pub const fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+
+    /// Reads the command queue in `State`. The queue is synchronized, so this is compatible
+    /// with everything except an exclusive access.
+    pub fn add_read_commands(&mut self) {
+        self.commands_conflict |= self.exclusive;
+        self.reads_commands = true;
+    }
+
+    #[must_use]
+    pub const fn reads_commands(&self) -> bool {
+        self.reads_commands
+    }
+
+    #[must_use]
+    pub const fn resources(&self) -> &StorageAccess {
+        &self.resources
+    }
+
+    pub const fn resources_mut(&mut self) -> &mut StorageAccess {
+        &mut self.resources
+    }
+
+    #[must_use]
+    pub const fn local_resources(&self) -> &StorageAccess {
+        &self.local_resources
+    }
+
+    pub const fn local_resources_mut(&mut self) -> &mut StorageAccess {
+        &mut self.local_resources
+    }
+
+    #[must_use]
+    pub const fn messages(&self) -> &StorageAccess {
+        &self.messages
+    }
+
+    pub const fn messages_mut(&mut self) -> &mut StorageAccess {
+        &mut self.messages
+    }
+
+    pub fn extend(&mut self, other: &Self) {
+        self.resources.extend(&other.resources);
+        self.local_resources.extend(&other.local_resources);
+        self.messages.extend(&other.messages);
+        self.reads_commands |= other.reads_commands;
+        self.exclusive |= other.exclusive;
+        self.commands_conflict |= other.commands_conflict;
+    }
+
+    /// An exclusive access is never compatible, not even with a system that has no params.
+    #[must_use]
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        if self.exclusive || other.exclusive {
+            return false;
+        }
+        self.resources.is_compatible(&other.resources)
+            && self.local_resources.is_compatible(&other.local_resources)
+            && self.messages.is_compatible(&other.messages)
+    }
+
+    /// The params that borrow the same data in conflicting ways, see [`AccessConflict`].
+    #[must_use]
+    pub fn conflicts(&self) -> Vec<AccessConflict> {
+        [
+            (Storage::Resources, &self.resources),
+            (Storage::LocalResources, &self.local_resources),
+            (Storage::Messages, &self.messages),
+        ]
+        .into_iter()
+        .flat_map(|(storage, access)| {
+            access
+                .conflicts
+                .iter()
+                .map(move |&type_name| AccessConflict { storage, type_name })
+        })
+        .chain(self.commands_conflict.then_some(AccessConflict {
+            storage: Storage::CommandQueue,
+            type_name: None,
+        }))
+        .collect()
+    }
+
+    /// Local resources are not `Send`, so systems that access them must run on the main thread.
+    #[must_use]
+    pub fn requires_main_thread(&self) -> bool {
+        !self.local_resources.is_empty()
+    }
+}
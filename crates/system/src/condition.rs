@@ -0,0 +1,264 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::{
+    Access, BoxedError, IntoSystem, System, SystemParam, SystemParamFunction, assert_no_conflicts,
+};
+use core::any::type_name;
+use core::marker::PhantomData;
+use limnus_system_state::{State, StateCell};
+use std::sync::{Mutex, PoisonError};
+
+/// Decides if a system should run, see [`IntoSystem::run_if`].
+pub trait Condition: 'static {
+    fn evaluate(&self, state: &mut State) -> bool;
+
+    /// Evaluates the condition through a [`StateCell`], see [`System::run_shared`].
+    ///
+    /// The default borrows the whole `State` and calls [`Self::evaluate`], which is only correct for
+    /// the default, exclusive, access. Conditions that override [`Self::access`] must override this too.
+    ///
+    /// # Safety
+    /// Nothing else may borrow what the condition writes, or write what the condition reads,
+    /// see [`Self::access`], until it returns.
+    unsafe fn evaluate_shared(&self, state: StateCell<'_>) -> bool {
+        // SAFETY: The access is exclusive, so the caller has made sure that nothing else borrows the state
+        self.evaluate(unsafe { state.state_mut() })
+    }
+
+    /// What the condition reads and writes, see [`System::access`].
+    fn access(&self) -> Access {
+        Access::exclusive()
+    }
+}
+
+/// Marker for [`IntoCondition`] implemented by conditions that are already a [`Condition`].
+pub struct IsCondition;
+
+/// Convert to a condition. Implemented for functions with only system params that return `bool`,
+/// and for conditions combined with [`IntoCondition::and`], [`IntoCondition::or`] and [`IntoCondition::not`].
+pub trait IntoCondition<Params>: Sized {
+    type Condition: Condition;
+
+    fn into_condition(self) -> Self::Condition;
+
+    /// True if both conditions are true. `other` is not evaluated if `self` is false.
+    fn and<C, OtherParams>(self, other: C) -> AndCondition<Self::Condition, C::Condition>
+    where
+        C: IntoCondition<OtherParams>,
+    {
+        AndCondition {
+            first: self.into_condition(),
+            second: other.into_condition(),
+        }
+    }
+
+    /// True if any of the conditions is true. `other` is not evaluated if `self` is true.
+    fn or<C, OtherParams>(self, other: C) -> OrCondition<Self::Condition, C::Condition>
+    where
+        C: IntoCondition<OtherParams>,
+    {
+        OrCondition {
+            first: self.into_condition(),
+            second: other.into_condition(),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    fn not(self) -> NotCondition<Self::Condition> {
+        NotCondition {
+            condition: self.into_condition(),
+        }
+    }
+}
+
+impl<F, Params: SystemParam> IntoCondition<Params> for F
+where
+    F: SystemParamFunction<Params, Out = bool>,
+{
+    type Condition = FunctionCondition<F, Params>;
+
+    /// # Panics
+    /// If two params borrow the same data and at least one of them borrows it mutably.
+    fn into_condition(self) -> Self::Condition {
+        assert_no_conflicts::<Params>("condition", type_name::<F>());
+        FunctionCondition {
+            function: Mutex::new((self, Params::Local::default())),
+            params: PhantomData,
+        }
+    }
+}
+
+impl<C: Condition> IntoCondition<IsCondition> for C {
+    type Condition = Self;
+
+    fn into_condition(self) -> Self::Condition {
+        self
+    }
+}
+
+/// Wraps a condition function with params. The condition is false if a param could not be fetched.
+pub struct FunctionCondition<F: 'static, Params: SystemParam> {
+    function: Mutex<(F, Params::Local)>,
+    params: PhantomData<fn() -> Params>,
+}
+
+impl<F, Params: SystemParam> Condition for FunctionCondition<F, Params>
+where
+    F: SystemParamFunction<Params, Out = bool>,
+{
+    fn evaluate(&self, state: &mut State) -> bool {
+        // SAFETY: The whole state is borrowed
+        unsafe { self.evaluate_shared(StateCell::new(state)) }
+    }
+
+    unsafe fn evaluate_shared(&self, state: StateCell<'_>) -> bool {
+        let mut function = self.function.lock().unwrap_or_else(PoisonError::into_inner);
+        let (function, local) = &mut *function;
+        // SAFETY: The caller keeps others from conflicting with `access`, and `into_condition` checked
+        // that the params do not conflict with each other
+        unsafe { function.run(state, local) }.unwrap_or(false)
+    }
+
+    fn access(&self) -> Access {
+        let mut access = Access::default();
+        Params::access(&mut access);
+        access
+    }
+}
+
+pub struct AndCondition<A: Condition, B: Condition> {
+    first: A,
+    second: B,
+}
+
+impl<A: Condition, B: Condition> Condition for AndCondition<A, B> {
+    fn evaluate(&self, state: &mut State) -> bool {
+        self.first.evaluate(state) && self.second.evaluate(state)
+    }
+
+    unsafe fn evaluate_shared(&self, state: StateCell<'_>) -> bool {
+        unsafe { self.first.evaluate_shared(state) && self.second.evaluate_shared(state) }
+    }
+
+    fn access(&self) -> Access {
+        let mut access = self.first.access();
+        access.extend(&self.second.access());
+        access
+    }
+}
+
+pub struct OrCondition<A: Condition, B: Condition> {
+    first: A,
+    second: B,
+}
+
+impl<A: Condition, B: Condition> Condition for OrCondition<A, B> {
+    fn evaluate(&self, state: &mut State) -> bool {
+        self.first.evaluate(state) || self.second.evaluate(state)
+    }
+
+    unsafe fn evaluate_shared(&self, state: StateCell<'_>) -> bool {
+        unsafe { self.first.evaluate_shared(state) || self.second.evaluate_shared(state) }
+    }
+
+    fn access(&self) -> Access {
+        let mut access = self.first.access();
+        access.extend(&self.second.access());
+        access
+    }
+}
+
+pub struct NotCondition<C: Condition> {
+    condition: C,
+}
+
+impl<C: Condition> Condition for NotCondition<C> {
+    fn evaluate(&self, state: &mut State) -> bool {
+        !self.condition.evaluate(state)
+    }
+
+    unsafe fn evaluate_shared(&self, state: StateCell<'_>) -> bool {
+        !unsafe { self.condition.evaluate_shared(state) }
+    }
+
+    fn access(&self) -> Access {
+        self.condition.access()
+    }
+}
+
+/// A system that only runs when all of its conditions are true, created with [`IntoSystem::run_if`].
+///
+/// The conditions are evaluated in the order they were added, and evaluation stops at the first false condition.
+///
+/// The conditions must be `Send + Sync`, so the system can be added to a stage when `S` is.
+pub struct ConditionalSystem<S: System> {
+    system: S,
+    conditions: Vec<Box<dyn Condition + Send + Sync>>,
+}
+
+impl<S: System> ConditionalSystem<S> {
+    pub(crate) fn new(system: S) -> Self {
+        Self {
+            system,
+            conditions: Vec::new(),
+        }
+    }
+
+    /// Adds another condition that must be true for the system to run.
+    #[must_use]
+    pub fn run_if<C, Params>(mut self, condition: C) -> Self
+    where
+        C: IntoCondition<Params>,
+        C::Condition: Send + Sync,
+    {
+        self.conditions.push(Box::new(condition.into_condition()));
+        self
+    }
+}
+
+impl<S: System> System for ConditionalSystem<S> {
+    fn run(&self, state: &mut State) -> Result<(), BoxedError> {
+        for condition in &self.conditions {
+            if !condition.evaluate(state) {
+                return Ok(());
+            }
+        }
+
+        self.system.run(state)
+    }
+
+    unsafe fn run_shared(&self, state: StateCell<'_>) -> Result<(), BoxedError> {
+        for condition in &self.conditions {
+            if !unsafe { condition.evaluate_shared(state) } {
+                return Ok(());
+            }
+        }
+
+        unsafe { self.system.run_shared(state) }
+    }
+
+    fn access(&self) -> Access {
+        let mut access = self.system.access();
+        for condition in &self.conditions {
+            access.extend(&condition.access());
+        }
+        access
+    }
+
+    fn name(&self) -> &'static str {
+        self.system.name()
+    }
+}
+
+/// Marker for [`IntoSystem`] implemented by systems that are already a [`System`].
+pub struct IsSystem;
+
+impl<S: System> IntoSystem<IsSystem> for S {
+    type System = Self;
+
+    fn into_system(self) -> Self::System {
+        self
+    }
+}
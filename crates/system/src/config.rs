@@ -0,0 +1,114 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::{ConditionalSystem, IntoCondition, IntoSystem, System};
+
+/// A system together with its labels and ordering constraints, created with
+/// [`IntoSystem::label`], [`IntoSystem::before`] or [`IntoSystem::after`].
+///
+/// ```
+/// use limnus_system::IntoSystem;
+///
+/// fn read_input() {}
+/// fn move_player() {}
+///
+/// let input = read_input.label("input");
+/// let movement = move_player.after("input");
+/// ```
+///
+/// The system must be `Send + Sync`, since the parallel executors run it on other threads.
+pub struct SystemConfig {
+    system: Box<dyn System + Send + Sync>,
+    labels: Vec<&'static str>,
+    before: Vec<&'static str>,
+    after: Vec<&'static str>,
+}
+
+impl SystemConfig {
+    #[must_use]
+    pub fn new(system: Box<dyn System + Send + Sync>) -> Self {
+        Self {
+            system,
+            labels: Vec::new(),
+            before: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+
+    /// Adds a label, a system can have any number of labels and a label can be shared by many systems.
+    #[must_use]
+    pub fn label(mut self, label: &'static str) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    #[must_use]
+    pub fn before(mut self, label: &'static str) -> Self {
+        self.before.push(label);
+        self
+    }
+
+    #[must_use]
+    pub fn after(mut self, label: &'static str) -> Self {
+        self.after.push(label);
+        self
+    }
+
+    /// Adds a condition that must be true for the system to run.
+    #[must_use]
+    pub fn run_if<C, Params>(mut self, condition: C) -> Self
+    where
+        C: IntoCondition<Params>,
+        C::Condition: Send + Sync,
+    {
+        self.system = Box::new(ConditionalSystem::new(self.system).run_if(condition));
+        self
+    }
+
+    #[must_use]
+    pub fn system(&self) -> &(dyn System + Send + Sync) {
+        self.system.as_ref()
+    }
+
+    #[must_use]
+    pub fn labels(&self) -> &[&'static str] {
+        &self.labels
+    }
+
+    /// The labels of the systems that this system must run before.
+    #[must_use]
+    pub fn runs_before(&self) -> &[&'static str] {
+        &self.before
+    }
+
+    /// The labels of the systems that this system must run after.
+    #[must_use]
+    pub fn runs_after(&self) -> &[&'static str] {
+        &self.after
+    }
+}
+
+/// Anything that can be added to a stage: a system, or a system with labels and ordering constraints.
+pub trait IntoSystemConfig<Params> {
+    fn into_system_config(self) -> SystemConfig;
+}
+
+impl<F, Params> IntoSystemConfig<Params> for F
+where
+    F: IntoSystem<Params>,
+    F::System: Send + Sync,
+{
+    fn into_system_config(self) -> SystemConfig {
+        SystemConfig::new(Box::new(self.into_system()))
+    }
+}
+
+/// Marker for [`IntoSystemConfig`] implemented by [`SystemConfig`].
+pub struct IsSystemConfig;
+
+impl IntoSystemConfig<IsSystemConfig> for SystemConfig {
+    fn into_system_config(self) -> SystemConfig {
+        self
+    }
+}
@@ -0,0 +1,114 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use limnus_resource::Resource;
+use limnus_system_state::State;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use tracing::error;
+
+/// The error returned by a failed system.
+pub type BoxedError = Box<dyn Error + Send + Sync>;
+
+/// What a system function can return: `()`, or `Result<(), E>` for systems that can fail.
+pub trait SystemOutput {
+    /// # Errors
+    /// If the system failed.
+    fn into_result(self) -> Result<(), BoxedError>;
+}
+
+impl SystemOutput for () {
+    fn into_result(self) -> Result<(), BoxedError> {
+        Ok(())
+    }
+}
+
+impl<E: Into<BoxedError>> SystemOutput for Result<(), E> {
+    fn into_result(self) -> Result<(), BoxedError> {
+        self.map_err(Into::into)
+    }
+}
+
+/// An error returned by a system, together with where it was run.
+#[derive(Debug)]
+pub struct SystemError {
+    /// The [`crate::System::name`] of the system.
+    pub system: &'static str,
+    /// The name of the stage that ran the system.
+    pub stage: &'static str,
+    pub error: BoxedError,
+}
+
+impl Display for SystemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "system `{}` in stage `{}` failed: {}",
+            self.system, self.stage, self.error
+        )
+    }
+}
+
+impl Error for SystemError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+/// Resource that decides what happens with the errors returned by systems.
+///
+/// Without this resource, the errors are logged.
+#[derive(Default)]
+pub enum SystemErrorHandler {
+    #[default]
+    Log,
+    Panic,
+    Custom(Box<dyn Fn(SystemError) + Send + Sync>),
+}
+
+impl Debug for SystemErrorHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Log => write!(f, "Log"),
+            Self::Panic => write!(f, "Panic"),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Resource for SystemErrorHandler {}
+
+impl SystemErrorHandler {
+    /// A handler that calls `handler` with each error.
+    #[must_use]
+    pub fn custom(handler: impl Fn(SystemError) + Send + Sync + 'static) -> Self {
+        Self::Custom(Box::new(handler))
+    }
+
+    /// # Panics
+    /// If the handler is [`Self::Panic`].
+    pub fn handle(&self, error: SystemError) {
+        match self {
+            Self::Log => error!(
+                system = error.system,
+                stage = error.stage,
+                error = %error.error,
+                "system failed"
+            ),
+            Self::Panic => panic!("{error}"),
+            Self::Custom(handler) => handler(error),
+        }
+    }
+}
+
+/// Passes the error to the [`SystemErrorHandler`] resource, or logs it if there is none.
+///
+/// # Panics
+/// If the handler is [`SystemErrorHandler::Panic`].
+pub fn handle_system_error(state: &State, error: SystemError) {
+    match state.resources().get::<SystemErrorHandler>() {
+        Some(handler) => handler.handle(error),
+        None => SystemErrorHandler::Log.handle(error),
+    }
+}
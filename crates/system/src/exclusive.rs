@@ -0,0 +1,65 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::{Access, BoxedError, IntoSystem, System, SystemOutput};
+use core::any::type_name;
+use core::marker::PhantomData;
+use limnus_system_state::State;
+use std::sync::{Mutex, PoisonError};
+
+/// Marker for [`IntoSystem`] implemented by functions that take `&mut State`, and return `()` or `Result<(), E>`.
+pub struct IsExclusiveSystem;
+
+/// A system that gets the whole `State`, so it can insert and remove resources, register message types and so on.
+///
+/// It runs alone: no other system, and no borrows from other system params, are alive while it runs.
+///
+/// ```
+/// use limnus_system::IntoSystem;
+/// use limnus_system_state::State;
+///
+/// fn swap_messages(state: &mut State) {
+///     state.messages_mut().swap_all();
+/// }
+///
+/// let system = swap_messages.into_system();
+/// ```
+pub struct ExclusiveFunctionSystem<F, Out> {
+    function: Mutex<F>,
+    output: PhantomData<fn() -> Out>,
+}
+
+impl<F, Out> IntoSystem<(IsExclusiveSystem, Out)> for F
+where
+    F: FnMut(&mut State) -> Out + 'static,
+    Out: SystemOutput + 'static,
+{
+    type System = ExclusiveFunctionSystem<F, Out>;
+
+    fn into_system(self) -> Self::System {
+        ExclusiveFunctionSystem {
+            function: Mutex::new(self),
+            output: PhantomData,
+        }
+    }
+}
+
+impl<F, Out> System for ExclusiveFunctionSystem<F, Out>
+where
+    F: FnMut(&mut State) -> Out + 'static,
+    Out: SystemOutput + 'static,
+{
+    fn run(&self, state: &mut State) -> Result<(), BoxedError> {
+        (self.function.lock().unwrap_or_else(PoisonError::into_inner))(state).into_result()
+    }
+
+    /// Conflicts with every other system, which keeps the parallel executors from running anything else at the same time.
+    fn access(&self) -> Access {
+        Access::exclusive()
+    }
+
+    fn name(&self) -> &'static str {
+        type_name::<F>()
+    }
+}
@@ -2,12 +2,105 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
+mod access;
+mod condition;
+mod config;
+mod error;
+mod exclusive;
+mod missing;
+mod one_shot;
+mod pipe;
+
+pub use access::{Access, AccessConflict, Storage, StorageAccess};
+pub use condition::{
+    AndCondition, Condition, ConditionalSystem, FunctionCondition, IntoCondition, IsCondition,
+    IsSystem, NotCondition, OrCondition,
+};
+pub use config::{IntoSystemConfig, IsSystemConfig, SystemConfig};
+pub use error::{BoxedError, SystemError, SystemErrorHandler, SystemOutput, handle_system_error};
+pub use exclusive::{ExclusiveFunctionSystem, IsExclusiveSystem};
+pub use missing::{MissingParamPolicy, MissingParams, handle_missing_param};
+pub use one_shot::{OneShotSystems, RunSystemError, SystemId, run_system};
+pub use pipe::{In, IsPipedSystem, PipeFunction, PipeSystem, Piped};
+
+/// Re-exported for `#[derive(SystemParam)]`.
+pub use limnus_system_state::{State, StateCell};
+
+use crate::missing::MissingParam;
+use core::any::type_name;
 use core::marker::PhantomData;
-use limnus_system_state::State;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The most params a system function can have. Group params with `#[derive(SystemParam)]` to use more.
+pub const MAX_SYSTEM_PARAMS: usize = 16;
+
+/// Invokes `$impl` once for each number of params, from one up to [`MAX_SYSTEM_PARAMS`].
+macro_rules! all_param_counts {
+    ($impl:ident) => {
+        all_param_counts!(@step $impl [P1] P2 P3 P4 P5 P6 P7 P8 P9 P10 P11 P12 P13 P14 P15 P16);
+    };
+    (@step $impl:ident [$($done:ident)+] $next:ident $($rest:ident)*) => {
+        $impl!($($done),+);
+        all_param_counts!(@step $impl [$($done)+ $next] $($rest)*);
+    };
+    (@step $impl:ident [$($done:ident)+]) => {
+        $impl!($($done),+);
+    };
+}
+pub(crate) use all_param_counts;
 
 // Systems
 pub trait System: 'static {
-    fn run(&self, world: &mut State);
+    /// # Errors
+    /// If the system failed. The stage passes the error on to the [`SystemErrorHandler`].
+    fn run(&self, world: &mut State) -> Result<(), BoxedError>;
+
+    /// Runs the system through a [`StateCell`] that other systems, with compatible [`Self::access`],
+    /// use at the same time. This is how the parallel executors run systems.
+    ///
+    /// The default borrows the whole `State` and calls [`Self::run`], which is only correct for
+    /// the default, exclusive, access. Systems that override [`Self::access`] must override this too.
+    ///
+    /// # Errors
+    /// If the system failed, see [`Self::run`]. A system that is skipped because of a missing param
+    /// returns an error that is reported with [`handle_missing_param`], once no other system is running.
+    ///
+    /// # Safety
+    /// Nothing else may borrow what the system writes, or write what the system reads, see [`Self::access`],
+    /// until it returns.
+    unsafe fn run_shared(&self, state: StateCell<'_>) -> Result<(), BoxedError> {
+        // SAFETY: The access is exclusive, so the caller has made sure that nothing else borrows the state
+        self.run(unsafe { state.state_mut() })
+    }
+
+    /// What the system reads and writes, used to decide which systems can run in parallel.
+    fn access(&self) -> Access {
+        Access::exclusive()
+    }
+
+    /// Used in error messages and diagnostics. For function systems it is the path of the function.
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+}
+
+impl<S: System + ?Sized> System for Box<S> {
+    fn run(&self, world: &mut State) -> Result<(), BoxedError> {
+        self.as_ref().run(world)
+    }
+
+    unsafe fn run_shared(&self, state: StateCell<'_>) -> Result<(), BoxedError> {
+        unsafe { self.as_ref().run_shared(state) }
+    }
+
+    fn access(&self) -> Access {
+        self.as_ref().access()
+    }
+
+    fn name(&self) -> &'static str {
+        self.as_ref().name()
+    }
 }
 
 /// Convert to system (to create a trait object)
@@ -15,407 +108,284 @@ pub trait IntoSystem<Params> {
     type System: System;
 
     fn into_system(self) -> Self::System;
+
+    /// The system is only run when `condition` returns `true`. Chain more `run_if` calls to add conditions.
+    ///
+    /// ```
+    /// use limnus_system::{IntoCondition, IntoSystem};
+    ///
+    /// fn is_paused() -> bool { false }
+    /// fn is_loading() -> bool { true }
+    /// fn move_player() {}
+    ///
+    /// let system = move_player.run_if(is_paused.or(is_loading).not());
+    /// ```
+    fn run_if<C, ConditionParams>(self, condition: C) -> ConditionalSystem<Self::System>
+    where
+        Self: Sized,
+        C: IntoCondition<ConditionParams>,
+        C::Condition: Send + Sync,
+    {
+        ConditionalSystem::new(self.into_system()).run_if(condition)
+    }
+
+    /// Gives the system a label that other systems in the same stage can be ordered against.
+    fn label(self, label: &'static str) -> SystemConfig
+    where
+        Self: Sized,
+        Self::System: Send + Sync,
+    {
+        SystemConfig::new(Box::new(self.into_system())).label(label)
+    }
+
+    /// The system runs before all systems in the same stage that have the `label`.
+    fn before(self, label: &'static str) -> SystemConfig
+    where
+        Self: Sized,
+        Self::System: Send + Sync,
+    {
+        SystemConfig::new(Box::new(self.into_system())).before(label)
+    }
+
+    /// The system runs after all systems in the same stage that have the `label`.
+    fn after(self, label: &'static str) -> SystemConfig
+    where
+        Self: Sized,
+        Self::System: Send + Sync,
+    {
+        SystemConfig::new(Box::new(self.into_system())).after(label)
+    }
 }
 
-/// Convert any function with only system params into a system
+/// Convert any function with only system params, that returns `()` or `Result<(), E>`, into a system
 impl<F, Params: SystemParam> IntoSystem<Params> for F
 where
     F: SystemParamFunction<Params>,
+    F::Out: SystemOutput,
 {
     type System = FunctionSystem<F, Params>;
 
+    /// # Panics
+    /// If two params borrow the same data and at least one of them borrows it mutably.
     fn into_system(self) -> Self::System {
+        assert_no_conflicts::<Params>("system", type_name::<F>());
         FunctionSystem {
-            system: self,
+            system: Mutex::new((self, Params::Local::default())),
             params: PhantomData,
+            warned: Arc::default(),
         }
     }
 }
 
-/// Wraps a system with params
-pub struct FunctionSystem<F: 'static, Params: SystemParam> {
-    system: F,
-    params: PhantomData<Params>,
+/// Params hand out references into `State`, so two params may not borrow the same data
+/// if one of them borrows it mutably. Checked once, when the system is created, instead of on each fetch.
+pub(crate) fn assert_no_conflicts<Params: SystemParam>(kind: &str, name: &str) {
+    let mut access = Access::default();
+    Params::access(&mut access);
+    assert_access_has_no_conflicts(&access, kind, name);
 }
 
-/// Implement `System` for the `FunctionSystem` wrapper
-impl<F, Params: SystemParam> System for FunctionSystem<F, Params>
-where
-    F: SystemParamFunction<Params>,
-{
-    fn run(&self, state: &mut State) {
-        SystemParamFunction::run(&self.system, state);
+pub(crate) fn assert_access_has_no_conflicts(access: &Access, kind: &str, name: &str) {
+    if let Some(conflict) = access.conflicts().first() {
+        panic!("{kind} `{name}` has conflicting params: {conflict}");
     }
 }
 
-/// A function with only `SystemParam`, which is the only thing that is supported (and unit function)
-trait SystemParamFunction<Params: SystemParam>: 'static {
-    fn run(&self, state: &mut State);
+/// Wraps a system with params, together with the data that its params keep between runs, like [`SystemParam::Local`].
+///
+/// Each instance has its own data, so the same function can be added many times without sharing it.
+pub struct FunctionSystem<F: 'static, Params: SystemParam> {
+    system: Mutex<(F, Params::Local)>,
+    params: PhantomData<fn() -> Params>,
+    warned: Arc<AtomicBool>,
 }
 
-/// Support for unit function, for convenience
-impl<F> SystemParamFunction<()> for F
+/// Implement `System` for the `FunctionSystem` wrapper
+impl<F, Params: SystemParam> System for FunctionSystem<F, Params>
 where
-    F: Fn() + 'static,
+    F: SystemParamFunction<Params>,
+    F::Out: SystemOutput,
 {
-    fn run(&self, _state: &mut State) {
-        self(); // We don't need state, since we are just calling without any parameter
+    /// A system that is skipped because of a missing param is not an error, see [`MissingParamPolicy`].
+    ///
+    /// # Panics
+    /// If a param is missing and the [`MissingParamPolicy`] is [`MissingParamPolicy::Panic`].
+    fn run(&self, state: &mut State) -> Result<(), BoxedError> {
+        // SAFETY: The whole state is borrowed
+        let result = unsafe { self.run_shared(StateCell::new(state)) };
+        handle_missing_param(state, result)
     }
-}
-
-/// implement `SystemParamFunction` for function with one parameter
-impl<F, P1: SystemParam<Item = P1>> SystemParamFunction<(P1,)> for F
-where
-    F: Fn(P1) + 'static,
-{
-    fn run(&self, world: &mut State) {
-        let Some(v1) = P1::get(world) else { return };
 
-        self(v1);
+    unsafe fn run_shared(&self, state: StateCell<'_>) -> Result<(), BoxedError> {
+        let result = {
+            let mut system = self.system.lock().unwrap_or_else(PoisonError::into_inner);
+            let (function, local) = &mut *system;
+            // SAFETY: The caller keeps others from conflicting with `access`, and `into_system` checked
+            // that the params do not conflict with each other
+            unsafe { function.run(state, local) }
+        };
+        match result {
+            Ok(output) => output.into_result(),
+            Err(param) => Err(Box::new(MissingParam::new(
+                self.name(),
+                param,
+                &self.warned,
+            ))),
+        }
     }
-}
-
-/// implement `SystemParamFunction` for function with two parameters
-impl<F, P1: SystemParam<Item = P1>, P2: SystemParam<Item = P2>> SystemParamFunction<(P1, P2)> for F
-where
-    F: Fn(P1, P2) + 'static,
-{
-    fn run(&self, world: &mut State) {
-        let Some(v1) = P1::get(world) else { return };
-        let Some(v2) = P2::get(world) else { return };
 
-        self(v1, v2);
+    fn access(&self) -> Access {
+        let mut access = Access::default();
+        Params::access(&mut access);
+        access
     }
-}
 
-/// implement `SystemParamFunction` for function with three parameters
-impl<F, P1: SystemParam<Item = P1>, P2: SystemParam<Item = P2>, P3: SystemParam<Item = P3>>
-    SystemParamFunction<(P1, P2, P3)> for F
-where
-    F: Fn(P1, P2, P3) + 'static,
-{
-    fn run(&self, world: &mut State) {
-        let Some(v1) = P1::get(world) else { return };
-        let Some(v2) = P2::get(world) else { return };
-        let Some(v3) = P3::get(world) else { return };
-
-        self(v1, v2, v3);
+    fn name(&self) -> &'static str {
+        type_name::<F>()
     }
 }
 
-/// implement `SystemParamFunction` for function with four parameters
-impl<
-    F,
-    P1: SystemParam<Item = P1>,
-    P2: SystemParam<Item = P2>,
-    P3: SystemParam<Item = P3>,
-    P4: SystemParam<Item = P4>,
-> SystemParamFunction<(P1, P2, P3, P4)> for F
-where
-    F: Fn(P1, P2, P3, P4) + 'static,
-{
-    fn run(&self, world: &mut State) {
-        let Some(v1) = P1::get(world) else { return };
-        let Some(v2) = P2::get(world) else { return };
-        let Some(v3) = P3::get(world) else { return };
-        let Some(v4) = P4::get(world) else { return };
-
-        self(v1, v2, v3, v4);
-    }
+/// A function with only `SystemParam`, which is the only thing that is supported (and unit function).
+///
+/// Returns the type name of the first param that could not be fetched, if the function was not called.
+pub trait SystemParamFunction<Params: SystemParam>: 'static {
+    type Out;
+
+    /// # Errors
+    /// If a param is missing.
+    ///
+    /// # Safety
+    /// See [`SystemParam::get`], for all the params.
+    unsafe fn run(
+        &mut self,
+        state: StateCell<'_>,
+        local: &mut Params::Local,
+    ) -> Result<Self::Out, &'static str>;
 }
 
-/// implement `SystemParamFunction` for function with five parameters
-impl<
-    F,
-    P1: SystemParam<Item = P1>,
-    P2: SystemParam<Item = P2>,
-    P3: SystemParam<Item = P3>,
-    P4: SystemParam<Item = P4>,
-    P5: SystemParam<Item = P5>,
-> SystemParamFunction<(P1, P2, P3, P4, P5)> for F
+/// Support for unit function, for convenience
+impl<F, Out> SystemParamFunction<()> for F
 where
-    F: Fn(P1, P2, P3, P4, P5) + 'static,
+    F: FnMut() -> Out + 'static,
 {
-    fn run(&self, world: &mut State) {
-        let Some(v1) = P1::get(world) else { return };
-        let Some(v2) = P2::get(world) else { return };
-        let Some(v3) = P3::get(world) else { return };
-        let Some(v4) = P4::get(world) else { return };
-        let Some(v5) = P5::get(world) else { return };
-
-        self(v1, v2, v3, v4, v5);
-    }
-}
+    type Out = Out;
 
-/// implement `SystemParamFunction` for function with six parameters
-impl<
-    F,
-    P1: SystemParam<Item = P1>,
-    P2: SystemParam<Item = P2>,
-    P3: SystemParam<Item = P3>,
-    P4: SystemParam<Item = P4>,
-    P5: SystemParam<Item = P5>,
-    P6: SystemParam<Item = P6>,
-> SystemParamFunction<(P1, P2, P3, P4, P5, P6)> for F
-where
-    F: Fn(P1, P2, P3, P4, P5, P6) + 'static,
-{
-    fn run(&self, world: &mut State) {
-        let Some(v1) = P1::get(world) else { return };
-        let Some(v2) = P2::get(world) else { return };
-        let Some(v3) = P3::get(world) else { return };
-        let Some(v4) = P4::get(world) else { return };
-        let Some(v5) = P5::get(world) else { return };
-        let Some(v6) = P6::get(world) else { return };
-
-        self(v1, v2, v3, v4, v5, v6);
+    unsafe fn run(&mut self, _state: StateCell<'_>, _local: &mut ()) -> Result<Out, &'static str> {
+        Ok(self()) // We don't need state, since we are just calling without any parameter
     }
 }
 
-/// implement `SystemParamFunction` for function with seven parameters
-impl<
-    F,
-    P1: SystemParam<Item = P1>,
-    P2: SystemParam<Item = P2>,
-    P3: SystemParam<Item = P3>,
-    P4: SystemParam<Item = P4>,
-    P5: SystemParam<Item = P5>,
-    P6: SystemParam<Item = P6>,
-    P7: SystemParam<Item = P7>,
-> SystemParamFunction<(P1, P2, P3, P4, P5, P6, P7)> for F
-where
-    F: Fn(P1, P2, P3, P4, P5, P6, P7) + 'static,
-{
-    fn run(&self, world: &mut State) {
-        let Some(v1) = P1::get(world) else { return };
-        let Some(v2) = P2::get(world) else { return };
-        let Some(v3) = P3::get(world) else { return };
-        let Some(v4) = P4::get(world) else { return };
-        let Some(v5) = P5::get(world) else { return };
-        let Some(v6) = P6::get(world) else { return };
-        let Some(v7) = P7::get(world) else { return };
-
-        self(v1, v2, v3, v4, v5, v6, v7);
-    }
+/// The `Local` of a tuple of params, as nested pairs since `Default` is only implemented for short tuples.
+macro_rules! nested_locals {
+    () => { () };
+    ($first:ident $(, $rest:ident)*) => {
+        (<$first as $crate::SystemParam>::Local, nested_locals!($($rest),*))
+    };
 }
-
-/// implement `SystemParamFunction` for function with eight parameters
-impl<
-    F,
-    P1: SystemParam<Item = P1>,
-    P2: SystemParam<Item = P2>,
-    P3: SystemParam<Item = P3>,
-    P4: SystemParam<Item = P4>,
-    P5: SystemParam<Item = P5>,
-    P6: SystemParam<Item = P6>,
-    P7: SystemParam<Item = P7>,
-    P8: SystemParam<Item = P8>,
-> SystemParamFunction<(P1, P2, P3, P4, P5, P6, P7, P8)> for F
-where
-    F: Fn(P1, P2, P3, P4, P5, P6, P7, P8) + 'static,
-{
-    fn run(&self, world: &mut State) {
-        let Some(v1) = P1::get(world) else { return };
-        let Some(v2) = P2::get(world) else { return };
-        let Some(v3) = P3::get(world) else { return };
-        let Some(v4) = P4::get(world) else { return };
-        let Some(v5) = P5::get(world) else { return };
-        let Some(v6) = P6::get(world) else { return };
-        let Some(v7) = P7::get(world) else { return };
-        let Some(v8) = P8::get(world) else { return };
-
-        self(v1, v2, v3, v4, v5, v6, v7, v8);
-    }
+pub(crate) use nested_locals;
+
+/// implement `SystemParamFunction` for functions with one or more parameters
+macro_rules! impl_system_param_function {
+    ($($param:ident),+) => {
+        impl<F, Out, $($param: SystemParam<Item = $param>),+> SystemParamFunction<($($param,)+)> for F
+        where
+            F: FnMut($($param),+) -> Out + 'static,
+        {
+            type Out = Out;
+
+            #[allow(non_snake_case)]
+            unsafe fn run(
+                &mut self,
+                state: StateCell<'_>,
+                local: &mut nested_locals!($($param),+),
+            ) -> Result<Out, &'static str> {
+                let rest = local;
+                $(
+                    let (param_local, rest) = rest;
+                    let $param = unsafe { $param::get(state, param_local) }.ok_or_else(type_name::<$param>)?;
+                )+
+                let () = rest;
+
+                Ok(self($($param),+))
+            }
+        }
+    };
 }
 
+all_param_counts!(impl_system_param_function);
+
 /// Abstraction of a parameter for a system function
 pub trait SystemParam: 'static {
     type Item;
 
-    fn get(world: &mut State) -> Option<Self::Item>;
+    /// Data that the param keeps between runs, owned by the system that has the param. `()` for most params.
+    type Local: Default + 'static;
+
+    /// Fetches the param. Most params borrow a single type in one of the storages of `state`,
+    /// or a whole storage, and nothing else.
+    ///
+    /// # Safety
+    /// Nothing else may borrow what the param writes, or write what the param reads, see [`Self::access`],
+    /// until the item is dropped.
+    unsafe fn get(state: StateCell<'_>, local: &mut Self::Local) -> Option<Self::Item>;
+
+    /// Adds what the param reads and writes. Params that do not override this are treated as
+    /// borrowing the whole `State`, see [`Access::add_exclusive`].
+    fn access(access: &mut Access) {
+        access.add_exclusive();
+    }
 }
 
 impl SystemParam for () {
     type Item = ();
+    type Local = ();
 
-    fn get(_world: &mut State) -> Option<Self::Item> {
+    unsafe fn get(_state: StateCell<'_>, _local: &mut ()) -> Option<Self::Item> {
         // it is easy to fetch, it is nothing `()`
         None
     }
-}
-
-/// implement one parameter for a `SystemParam`
-impl<T1> SystemParam for (T1,)
-where
-    T1: SystemParam,
-{
-    type Item = (T1::Item,);
-
-    fn get(world: &mut State) -> Option<Self::Item> {
-        T1::get(world).map(|t1| (t1,))
-    }
-}
-
-/// implement two parameters for a `SystemParam`
-impl<T1, T2> SystemParam for (T1, T2)
-where
-    T1: SystemParam,
-    T2: SystemParam,
-{
-    type Item = (T1::Item, T2::Item);
-
-    fn get(world: &mut State) -> Option<Self::Item> {
-        T1::get(world).zip(T2::get(world))
-    }
-}
-
-/// implement three parameters for a `SystemParam`
-impl<T1, T2, T3> SystemParam for (T1, T2, T3)
-where
-    T1: SystemParam,
-    T2: SystemParam,
-    T3: SystemParam,
-{
-    type Item = (T1::Item, T2::Item, T3::Item);
 
-    fn get(world: &mut State) -> Option<Self::Item> {
-        Some((T1::get(world)?, T2::get(world)?, T3::get(world)?))
-    }
+    fn access(_access: &mut Access) {}
 }
 
-/// implement four parameters for a `SystemParam`
-impl<T1, T2, T3, T4> SystemParam for (T1, T2, T3, T4)
-where
-    T1: SystemParam,
-    T2: SystemParam,
-    T3: SystemParam,
-    T4: SystemParam,
-{
-    type Item = (T1::Item, T2::Item, T3::Item, T4::Item);
-
-    fn get(world: &mut State) -> Option<Self::Item> {
-        Some((
-            T1::get(world)?,
-            T2::get(world)?,
-            T3::get(world)?,
-            T4::get(world)?,
-        ))
-    }
-}
+/// A param that is allowed to be missing, like `Option<Re<T>>`. The system is run with `None` instead of being skipped.
+impl<P: SystemParam<Item = P>> SystemParam for Option<P> {
+    type Item = Self;
+    type Local = P::Local;
 
-/// implement five parameters for a `SystemParam`
-impl<T1, T2, T3, T4, T5> SystemParam for (T1, T2, T3, T4, T5)
-where
-    T1: SystemParam,
-    T2: SystemParam,
-    T3: SystemParam,
-    T4: SystemParam,
-    T5: SystemParam,
-{
-    type Item = (T1::Item, T2::Item, T3::Item, T4::Item, T5::Item);
-
-    fn get(world: &mut State) -> Option<Self::Item> {
-        Some((
-            T1::get(world)?,
-            T2::get(world)?,
-            T3::get(world)?,
-            T4::get(world)?,
-            T5::get(world)?,
-        ))
+    unsafe fn get(state: StateCell<'_>, local: &mut Self::Local) -> Option<Self::Item> {
+        Some(unsafe { P::get(state, local) })
     }
-}
 
-/// implement six parameters for a `SystemParam`
-impl<T1, T2, T3, T4, T5, T6> SystemParam for (T1, T2, T3, T4, T5, T6)
-where
-    T1: SystemParam,
-    T2: SystemParam,
-    T3: SystemParam,
-    T4: SystemParam,
-    T5: SystemParam,
-    T6: SystemParam,
-{
-    type Item = (T1::Item, T2::Item, T3::Item, T4::Item, T5::Item, T6::Item);
-
-    fn get(world: &mut State) -> Option<Self::Item> {
-        Some((
-            T1::get(world)?,
-            T2::get(world)?,
-            T3::get(world)?,
-            T4::get(world)?,
-            T5::get(world)?,
-            T6::get(world)?,
-        ))
+    fn access(access: &mut Access) {
+        P::access(access);
     }
 }
 
-/// implement seven parameters for a `SystemParam`
-impl<T1, T2, T3, T4, T5, T6, T7> SystemParam for (T1, T2, T3, T4, T5, T6, T7)
-where
-    T1: SystemParam,
-    T2: SystemParam,
-    T3: SystemParam,
-    T4: SystemParam,
-    T5: SystemParam,
-    T6: SystemParam,
-    T7: SystemParam,
-{
-    type Item = (
-        T1::Item,
-        T2::Item,
-        T3::Item,
-        T4::Item,
-        T5::Item,
-        T6::Item,
-        T7::Item,
-    );
-
-    fn get(world: &mut State) -> Option<Self::Item> {
-        Some((
-            T1::get(world)?,
-            T2::get(world)?,
-            T3::get(world)?,
-            T4::get(world)?,
-            T5::get(world)?,
-            T6::get(world)?,
-            T7::get(world)?,
-        ))
-    }
+/// implement `SystemParam` for tuples of parameters
+macro_rules! impl_system_param_tuple {
+    ($($param:ident),+) => {
+        impl<$($param: SystemParam),+> SystemParam for ($($param,)+) {
+            type Item = ($($param::Item,)+);
+            type Local = nested_locals!($($param),+);
+
+            #[allow(non_snake_case)]
+            unsafe fn get(state: StateCell<'_>, local: &mut Self::Local) -> Option<Self::Item> {
+                let rest = local;
+                $(
+                    let (param_local, rest) = rest;
+                    let $param = unsafe { $param::get(state, param_local) }?;
+                )+
+                let () = rest;
+
+                Some(($($param,)+))
+            }
+
+            fn access(access: &mut Access) {
+                $($param::access(access);)+
+            }
+        }
+    };
 }
 
-/// implement seven parameters for a `SystemParam`
-impl<T1, T2, T3, T4, T5, T6, T7, T8> SystemParam for (T1, T2, T3, T4, T5, T6, T7, T8)
-where
-    T1: SystemParam,
-    T2: SystemParam,
-    T3: SystemParam,
-    T4: SystemParam,
-    T5: SystemParam,
-    T6: SystemParam,
-    T7: SystemParam,
-    T8: SystemParam,
-{
-    type Item = (
-        T1::Item,
-        T2::Item,
-        T3::Item,
-        T4::Item,
-        T5::Item,
-        T6::Item,
-        T7::Item,
-        T8::Item,
-    );
-
-    fn get(world: &mut State) -> Option<Self::Item> {
-        Some((
-            T1::get(world)?,
-            T2::get(world)?,
-            T3::get(world)?,
-            T4::get(world)?,
-            T5::get(world)?,
-            T6::get(world)?,
-            T7::get(world)?,
-            T8::get(world)?,
-        ))
-    }
-}
+all_param_counts!(impl_system_param_tuple);
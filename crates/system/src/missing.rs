@@ -0,0 +1,161 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::BoxedError;
+use limnus_resource::Resource;
+use limnus_system_state::State;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// What happens when a system is not run because one of its required params could not be fetched,
+/// usually because a resource was never inserted or a message type was never registered.
+///
+/// Use `Option<Re<T>>` and similar for params that are allowed to be missing.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum MissingParamPolicy {
+    /// Logs a warning the first time each system is skipped.
+    #[default]
+    WarnOnce,
+    Panic,
+    /// Only counts how many times each system was skipped, see [`MissingParams::count`].
+    Count,
+}
+
+/// Resource that selects the [`MissingParamPolicy`] and holds the counts for [`MissingParamPolicy::Count`].
+///
+/// Without this resource, the policy is [`MissingParamPolicy::WarnOnce`].
+#[derive(Debug, Default)]
+pub struct MissingParams {
+    policy: MissingParamPolicy,
+    counts: Mutex<HashMap<(&'static str, &'static str), u64>>,
+}
+
+impl Resource for MissingParams {}
+
+impl MissingParams {
+    #[must_use]
+    pub fn new(policy: MissingParamPolicy) -> Self {
+        Self {
+            policy,
+            counts: Mutex::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn policy(&self) -> MissingParamPolicy {
+        self.policy
+    }
+
+    /// How many times the system with the name was skipped because of a missing param.
+    #[must_use]
+    pub fn count(&self, system_name: &str) -> u64 {
+        self.counts
+            .lock()
+            .expect("missing params lock")
+            .iter()
+            .filter(|((system, _), _)| *system == system_name)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// The number of skipped runs for each system and missing param type.
+    #[must_use]
+    pub fn counts(&self) -> Vec<(&'static str, &'static str, u64)> {
+        self.counts
+            .lock()
+            .expect("missing params lock")
+            .iter()
+            .map(|(&(system, param), &count)| (system, param, count))
+            .collect()
+    }
+}
+
+/// Returned by [`crate::System::run_shared`] when a system is not run since one of its params is missing.
+///
+/// Reporting it reads the [`MissingParams`] resource, which the system has not declared in its access,
+/// so it is reported with [`handle_missing_param`] after the systems that run at the same time are done.
+#[derive(Debug)]
+pub(crate) struct MissingParam {
+    system: &'static str,
+    param: &'static str,
+    /// Shared with the system, so [`MissingParamPolicy::WarnOnce`] warns once for each system.
+    warned: Arc<AtomicBool>,
+}
+
+impl MissingParam {
+    pub(crate) fn new(system: &'static str, param: &'static str, warned: &Arc<AtomicBool>) -> Self {
+        Self {
+            system,
+            param,
+            warned: Arc::clone(warned),
+        }
+    }
+}
+
+impl Display for MissingParam {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "system `{}` is not run, since param `{}` is missing",
+            self.system, self.param
+        )
+    }
+}
+
+impl Error for MissingParam {}
+
+/// Reports the error according to the [`MissingParamPolicy`] if it is from a system with a missing param,
+/// which is not an error. Other results are returned as they are.
+///
+/// # Errors
+/// The error in `result`, if it is not from a missing param.
+///
+/// # Panics
+/// If a param is missing and the policy is [`MissingParamPolicy::Panic`].
+pub fn handle_missing_param(
+    state: &State,
+    result: Result<(), BoxedError>,
+) -> Result<(), BoxedError> {
+    let Err(error) = result else {
+        return Ok(());
+    };
+    let missing = error.downcast::<MissingParam>()?;
+
+    let missing_params = state.resources().get::<MissingParams>();
+    match missing_params
+        .map(MissingParams::policy)
+        .unwrap_or_default()
+    {
+        MissingParamPolicy::WarnOnce => {
+            if !missing.warned.swap(true, Ordering::Relaxed) {
+                warn!(
+                    system = missing.system,
+                    param = missing.param,
+                    "system is not run, since one of its params is missing"
+                );
+            }
+        }
+        MissingParamPolicy::Panic => {
+            panic!(
+                "system `{}` could not run, param `{}` is missing",
+                missing.system, missing.param
+            )
+        }
+        MissingParamPolicy::Count => {
+            if let Some(missing_params) = missing_params {
+                *missing_params
+                    .counts
+                    .lock()
+                    .expect("missing params lock")
+                    .entry((missing.system, missing.param))
+                    .or_default() += 1;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::{BoxedError, IntoSystem, System};
+use limnus_resource::Resource;
+use limnus_system_state::State;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+/// Identifies a system in [`OneShotSystems`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SystemId(u64);
+
+impl Display for SystemId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Resource with the systems that are only run when asked to, for example when a button is clicked,
+/// see [`run_system`].
+#[derive(Default)]
+pub struct OneShotSystems {
+    systems: HashMap<SystemId, Arc<dyn System + Send + Sync>>,
+    next_id: u64,
+}
+
+impl Debug for OneShotSystems {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.systems.iter().map(|(id, system)| (id, system.name())))
+            .finish()
+    }
+}
+
+impl Resource for OneShotSystems {}
+
+impl OneShotSystems {
+    /// Adds the system, which can then be run any number of times with the returned id.
+    ///
+    /// Each registration is a separate system, with its own [`crate::SystemParam::Local`] data.
+    pub fn register<F, Params>(&mut self, system: F) -> SystemId
+    where
+        F: IntoSystem<Params>,
+        F::System: Send + Sync,
+    {
+        let id = SystemId(self.next_id);
+        self.next_id += 1;
+        self.systems.insert(id, Arc::new(system.into_system()));
+        id
+    }
+
+    /// Returns `false` if there was no system with the id.
+    pub fn remove(&mut self, id: SystemId) -> bool {
+        self.systems.remove(&id).is_some()
+    }
+
+    #[must_use]
+    pub fn contains(&self, id: SystemId) -> bool {
+        self.systems.contains_key(&id)
+    }
+
+    #[must_use]
+    pub fn name(&self, id: SystemId) -> Option<&'static str> {
+        self.systems.get(&id).map(|system| system.name())
+    }
+
+    fn get(&self, id: SystemId) -> Option<Arc<dyn System + Send + Sync>> {
+        self.systems.get(&id).cloned()
+    }
+}
+
+#[derive(Debug)]
+pub enum RunSystemError {
+    /// The id was never registered, or the system has been removed.
+    NotRegistered(SystemId),
+    Failed {
+        system: &'static str,
+        error: BoxedError,
+    },
+}
+
+impl Display for RunSystemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotRegistered(id) => write!(f, "no one-shot system is registered with id {id}"),
+            Self::Failed { system, error } => {
+                write!(f, "one-shot system `{system}` failed: {error}")
+            }
+        }
+    }
+}
+
+impl Error for RunSystemError {}
+
+/// Runs the one-shot system with the id right away. The commands that it records are not applied.
+///
+/// The system may register, remove and run other one-shot systems, and even remove itself.
+///
+/// # Errors
+/// If there is no system with the id, or if the system returned an error.
+pub fn run_system(state: &mut State, id: SystemId) -> Result<(), RunSystemError> {
+    let system = state
+        .resources()
+        .get::<OneShotSystems>()
+        .and_then(|systems| systems.get(id))
+        .ok_or(RunSystemError::NotRegistered(id))?;
+
+    system.run(state).map_err(|error| RunSystemError::Failed {
+        system: system.name(),
+        error,
+    })
+}
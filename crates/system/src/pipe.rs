@@ -0,0 +1,286 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/swamp/limnus
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use crate::missing::{MissingParam, handle_missing_param};
+use crate::{
+    Access, BoxedError, IntoSystem, System, SystemOutput, SystemParam, SystemParamFunction,
+    all_param_counts, assert_access_has_no_conflicts, nested_locals,
+};
+use core::any::type_name;
+use core::marker::PhantomData;
+use limnus_system_state::{State, StateCell};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The first param of a function that is piped into, holding the output of the previous function, see [`PipeFunction::pipe`].
+pub struct In<T>(pub T);
+
+/// A function that can be part of a pipe: a system function, or a function whose first param is [`In`].
+///
+/// ```
+/// use limnus_system::{In, IntoSystem, PipeFunction};
+///
+/// fn parse() -> Result<u32, String> { "12".parse().map_err(|_| "not a number".to_string()) }
+/// fn log_error(In(result): In<Result<u32, String>>) {
+///     if let Err(err) = result {
+///         eprintln!("{err}");
+///     }
+/// }
+///
+/// let system = parse.pipe(log_error).into_system();
+/// ```
+pub trait PipeFunction<Input, Params>: 'static {
+    type Out;
+    /// The data that the params of the function keep between runs, see [`SystemParam::Local`].
+    type Local: Default + 'static;
+
+    /// Returns the type name of the first param that could not be fetched, if the function was not called.
+    ///
+    /// # Errors
+    /// If a param is missing.
+    ///
+    /// # Safety
+    /// See [`SystemParam::get`], for all the params.
+    unsafe fn run(
+        &mut self,
+        input: Input,
+        state: StateCell<'_>,
+        local: &mut Self::Local,
+    ) -> Result<Self::Out, &'static str>;
+
+    fn access(access: &mut Access);
+
+    fn name(&self) -> &'static str;
+
+    /// Passes the output of this function as the [`In`] of `next`. The pair can be piped further, or used as one system.
+    ///
+    /// The functions run one after the other, so their params may borrow the same data.
+    ///
+    /// # Panics
+    /// If two params of the same function borrow the same data and at least one of them borrows it mutably.
+    fn pipe<B, BParams>(self, next: B) -> Piped<Self, Params, B, BParams>
+    where
+        Self: Sized,
+        B: PipeFunction<Self::Out, BParams>,
+    {
+        let mut first = Access::default();
+        Self::access(&mut first);
+        assert_access_has_no_conflicts(&first, "system", self.name());
+        let mut second = Access::default();
+        B::access(&mut second);
+        assert_access_has_no_conflicts(&second, "system", next.name());
+        Piped {
+            first: self,
+            second: next,
+            params: PhantomData,
+        }
+    }
+}
+
+/// Any system function can start a pipe.
+impl<F, Params: SystemParam> PipeFunction<(), Params> for F
+where
+    F: SystemParamFunction<Params>,
+{
+    type Out = F::Out;
+    type Local = Params::Local;
+
+    unsafe fn run(
+        &mut self,
+        _input: (),
+        state: StateCell<'_>,
+        local: &mut Params::Local,
+    ) -> Result<F::Out, &'static str> {
+        unsafe { SystemParamFunction::run(self, state, local) }
+    }
+
+    fn access(access: &mut Access) {
+        Params::access(access);
+    }
+
+    fn name(&self) -> &'static str {
+        type_name::<F>()
+    }
+}
+
+/// Support for functions that only take the input
+impl<F, Input, Out> PipeFunction<Input, (In<Input>,)> for F
+where
+    F: FnMut(In<Input>) -> Out + 'static,
+{
+    type Out = Out;
+    type Local = ();
+
+    unsafe fn run(
+        &mut self,
+        input: Input,
+        _state: StateCell<'_>,
+        _local: &mut (),
+    ) -> Result<Out, &'static str> {
+        Ok(self(In(input)))
+    }
+
+    fn access(_access: &mut Access) {}
+
+    fn name(&self) -> &'static str {
+        type_name::<F>()
+    }
+}
+
+/// implement `PipeFunction` for functions that take the input and one or more system params
+macro_rules! impl_pipe_function {
+    ($($param:ident),+) => {
+        impl<F, Input, Out, $($param: SystemParam<Item = $param>),+> PipeFunction<Input, (In<Input>, $($param,)+)> for F
+        where
+            F: FnMut(In<Input>, $($param),+) -> Out + 'static,
+        {
+            type Out = Out;
+            type Local = nested_locals!($($param),+);
+
+            #[allow(non_snake_case)]
+            unsafe fn run(
+                &mut self,
+                input: Input,
+                state: StateCell<'_>,
+                local: &mut Self::Local,
+            ) -> Result<Out, &'static str> {
+                let rest = local;
+                $(
+                    let (param_local, rest) = rest;
+                    let $param = unsafe { $param::get(state, param_local) }.ok_or_else(type_name::<$param>)?;
+                )+
+                let () = rest;
+
+                Ok(self(In(input), $($param),+))
+            }
+
+            fn access(access: &mut Access) {
+                $($param::access(access);)+
+            }
+
+            fn name(&self) -> &'static str {
+                type_name::<F>()
+            }
+        }
+    };
+}
+
+all_param_counts!(impl_pipe_function);
+
+/// Two functions where the output of the first is the input of the second, created with [`PipeFunction::pipe`].
+pub struct Piped<A, AParams, B, BParams> {
+    first: A,
+    second: B,
+    params: PhantomData<fn() -> (AParams, BParams)>,
+}
+
+impl<A, AInput, AParams, B, BParams> PipeFunction<AInput, (AParams, BParams)>
+    for Piped<A, AParams, B, BParams>
+where
+    A: PipeFunction<AInput, AParams>,
+    B: PipeFunction<A::Out, BParams>,
+    AParams: 'static,
+    BParams: 'static,
+{
+    type Out = B::Out;
+    type Local = (A::Local, B::Local);
+
+    unsafe fn run(
+        &mut self,
+        input: AInput,
+        state: StateCell<'_>,
+        local: &mut Self::Local,
+    ) -> Result<B::Out, &'static str> {
+        // The params of the first function are dropped before the second one is fetched
+        let output = unsafe { self.first.run(input, state, &mut local.0) }?;
+        unsafe { self.second.run(output, state, &mut local.1) }
+    }
+
+    /// The params of the two functions are never borrowed at the same time, so conflicts between them are not recorded.
+    fn access(access: &mut Access) {
+        let mut second = Access::default();
+        B::access(&mut second);
+        A::access(access);
+        access.extend(&second);
+    }
+
+    /// The name of the first function, since the later ones are usually reusable adapters.
+    fn name(&self) -> &'static str {
+        self.first.name()
+    }
+}
+
+/// Marker for [`IntoSystem`] implemented by [`Piped`].
+pub struct IsPipedSystem;
+
+impl<A, AParams, B, BParams> IntoSystem<IsPipedSystem> for Piped<A, AParams, B, BParams>
+where
+    Self: PipeFunction<(), (AParams, BParams)>,
+    <Self as PipeFunction<(), (AParams, BParams)>>::Out: SystemOutput,
+    AParams: 'static,
+    BParams: 'static,
+{
+    type System = PipeSystem<Self, (AParams, BParams)>;
+
+    fn into_system(self) -> Self::System {
+        PipeSystem {
+            function: Mutex::new((self, Default::default())),
+            params: PhantomData,
+            warned: Arc::default(),
+        }
+    }
+}
+
+/// A pipe of functions that is run as one system.
+pub struct PipeSystem<P: PipeFunction<(), Params>, Params> {
+    function: Mutex<(P, P::Local)>,
+    params: PhantomData<fn() -> Params>,
+    warned: Arc<AtomicBool>,
+}
+
+impl<P, Params> System for PipeSystem<P, Params>
+where
+    P: PipeFunction<(), Params>,
+    P::Out: SystemOutput,
+    Params: 'static,
+{
+    /// Stops at the first function with a missing param, see [`crate::MissingParamPolicy`].
+    ///
+    /// # Panics
+    /// If a param is missing and the [`crate::MissingParamPolicy`] is [`crate::MissingParamPolicy::Panic`].
+    fn run(&self, state: &mut State) -> Result<(), BoxedError> {
+        // SAFETY: The whole state is borrowed
+        let result = unsafe { self.run_shared(StateCell::new(state)) };
+        handle_missing_param(state, result)
+    }
+
+    unsafe fn run_shared(&self, state: StateCell<'_>) -> Result<(), BoxedError> {
+        let (result, name) = {
+            let mut function = self.function.lock().unwrap_or_else(PoisonError::into_inner);
+            let (function, local) = &mut *function;
+            // SAFETY: The caller keeps others from conflicting with `access`, and `pipe` checked, for each
+            // function in the pipe, that its params do not conflict with each other
+            let result = unsafe { function.run((), state, local) };
+            (result, function.name())
+        };
+        match result {
+            Ok(output) => output.into_result(),
+            Err(param) => Err(Box::new(MissingParam::new(name, param, &self.warned))),
+        }
+    }
+
+    fn access(&self) -> Access {
+        let mut access = Access::default();
+        P::access(&mut access);
+        access
+    }
+
+    fn name(&self) -> &'static str {
+        self.function
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .0
+            .name()
+    }
+}
@@ -26,6 +26,7 @@ pub struct WindowState {
     requested_surface_size: UVec2,
     minimal_surface_size: UVec2,
     physical_surface_size: dpi::PhysicalSize<u32>,
+    return_value: Option<AppReturnValue>,
 }
 
 impl AppHandler for WindowState {
@@ -186,6 +187,10 @@ impl AppHandler for WindowState {
         _inner_size_writer: winit::event::InnerSizeWriter,
     ) {
     }
+
+    fn exiting(&mut self) {
+        self.return_value = Some(self.app.lock().unwrap().shutdown());
+    }
 }
 
 pub fn runner(mut app: App) -> AppReturnValue {
@@ -222,11 +227,14 @@ pub fn runner(mut app: App) -> AppReturnValue {
             requested_surface_size.x as u32,
             requested_surface_size.y as u32,
         ),
+        return_value: None,
     };
 
     limnus_window::WindowRunner::run_app(&mut state, title.as_str()).expect("run_app failed");
 
     debug!("we returned, that is not guaranteed for all platforms");
 
-    AppReturnValue::Value(0)
+    state
+        .return_value
+        .unwrap_or_else(|| state.app.lock().unwrap().shutdown())
 }
@@ -112,6 +112,11 @@ impl AppHandler for ExampleWindowHandler {
             .request_inner_size(PhysicalSize::new(800, 500))
             .unwrap()
     }
+
+    // Lifecycle
+    fn exiting(&mut self) {
+        info!("exiting");
+    }
 }
 
 fn main() {
@@ -173,6 +173,14 @@ pub trait AppHandler {
     ///   rendering.
     /// - `inner_size_writer`: A writer to update the inner size.
     fn scale_factor_changed(&mut self, scale_factor: f64, inner_size_writer: InnerSizeWriter);
+
+    // Lifecycle
+
+    /// Called once when the event loop is exiting.
+    ///
+    /// On some platforms `run_app` never returns, so this is the last chance to clean up.
+    /// Does nothing by default.
+    fn exiting(&mut self) {}
 }
 
 struct App<'a> {
@@ -448,7 +456,9 @@ impl ApplicationHandler for App<'_> {
 
     fn suspended(&mut self, _: &ActiveEventLoop) {}
 
-    fn exiting(&mut self, _: &ActiveEventLoop) {}
+    fn exiting(&mut self, _: &ActiveEventLoop) {
+        self.handler.exiting();
+    }
 }
 
 /// A struct responsible for managing the application window lifecycle.